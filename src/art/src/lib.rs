@@ -33,6 +33,8 @@ pub mod layers {
 }
 
 pub mod tiles {
+    use graphics::{TextureSettings};
+
     use ::{Name, Size, Tint, RenderType, Sprite};
 
     pub const NAME: Name = "tiles.png";
@@ -41,4 +43,8 @@ pub mod tiles {
     pub const ID: RenderType = 0;
 
     pub const EMPTY: Sprite = &[0.0, 0.0, 32.0, 31.5];
+
+    pub fn make_texture_settings() -> TextureSettings {
+        TextureSettings::new_sprite_sheet()
+    }
 }
@@ -0,0 +1,133 @@
+use std::path::{PathBuf};
+
+use dependencies::getopts::{Options as GetOpts, Matches};
+
+use core::{Config, Options};
+use core::config::{WindowConfig};
+
+pub enum Command {
+    Run(Config, Options),
+    Help(String),
+    Version(String),
+}
+
+fn build_opts() -> GetOpts {
+    let mut opts = GetOpts::new();
+    opts.optopt("c", "config", "load the startup config from FILE instead of config.toml", "FILE");
+    opts.optopt("w", "windows", "override the number of windows", "COUNT");
+    opts.optopt("r", "resolution", "override the resolution of every window", "WIDTHxHEIGHT");
+    opts.optopt("", "headless", "run without SDL or OpenGL for FRAMES frames", "FRAMES");
+    opts.optopt("s", "seed", "seed the game rng", "SEED");
+    opts.optopt("l", "load", "start from a save file", "FILE");
+    opts.optopt("", "record", "record input to a replay file", "FILE");
+    opts.optopt("", "replay", "play back input from a replay file", "FILE");
+    opts.optflag("V", "version", "print version and build info");
+    opts.optflag("h", "help", "print this help menu");
+    opts
+}
+
+pub fn version() -> String {
+    format!(
+        "{} {} ({} build, {})",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION"),
+        if cfg!(debug_assertions) { "debug" } else { "release" },
+        env!("CARGO_PKG_AUTHORS")
+    )
+}
+
+pub fn parse_args(args: Vec<String>) -> Result<Command, String> {
+    let program = args.get(0).cloned().unwrap_or(env!("CARGO_PKG_NAME").to_string());
+    let opts = build_opts();
+
+    let matches = try!(opts.parse(&args[1..]).map_err(|err| format!("{}\n{}", err, opts.short_usage(&program))));
+
+    if matches.opt_present("help") {
+        return Ok(Command::Help(opts.usage(&format!("Usage: {} [options]", program))));
+    }
+
+    if matches.opt_present("version") {
+        return Ok(Command::Version(version()));
+    }
+
+    if !matches.free.is_empty() {
+        return Err(format!("unexpected argument: {}\n{}", matches.free[0], opts.short_usage(&program)));
+    }
+
+    let mut config = try!(match matches.opt_str("config") {
+        Some(path) => Config::load(path),
+        None => Config::load_default(),
+    }.map_err(|err| err.to_string()));
+
+    try!(apply_window_overrides(&matches, &mut config));
+
+    let mut options = Options::new();
+
+    if let Some(frames) = try!(parse_opt::<u64>(&matches, "headless")) {
+        options.set_headless_frames(frames);
+    }
+
+    if let Some(seed) = try!(parse_opt::<u64>(&matches, "seed")) {
+        options.set_seed(seed);
+    }
+
+    if let Some(path) = matches.opt_str("load") {
+        options.set_save_file(PathBuf::from(path));
+    }
+
+    if let Some(path) = matches.opt_str("record") {
+        options.set_record_file(PathBuf::from(path));
+    }
+
+    if let Some(path) = matches.opt_str("replay") {
+        options.set_replay_file(PathBuf::from(path));
+    }
+
+    if matches.opt_present("record") && matches.opt_present("replay") {
+        return Err("--record and --replay can not be used together".to_string());
+    }
+
+    Ok(Command::Run(config, options))
+}
+
+fn parse_opt<T>(matches: &Matches, name: &str) -> Result<Option<T>, String>
+where T: ::std::str::FromStr {
+    match matches.opt_str(name) {
+        Some(value) => value.parse().map(Some).map_err(|_| format!("invalid value for --{}: {:?}", name, value)),
+        None => Ok(None),
+    }
+}
+
+fn apply_window_overrides(matches: &Matches, config: &mut Config) -> Result<(), String> {
+    if let Some(count) = try!(parse_opt::<usize>(matches, "windows")) {
+        if count == 0 {
+            return Err("--windows must be at least 1".to_string());
+        }
+
+        let windows = config.get_mut_windows();
+        windows.truncate(count);
+        while windows.len() < count {
+            let last = windows[windows.len() - 1].clone();
+            windows.push(WindowConfig::new(format!("Window {}", windows.len() + 1), last.get_width(), last.get_height(), last.get_clear_color()));
+        }
+    }
+
+    if let Some(resolution) = matches.opt_str("resolution") {
+        let (width, height) = try!(parse_resolution(&resolution).ok_or_else(|| format!("invalid value for --resolution: {:?}, expected WIDTHxHEIGHT", resolution)));
+
+        for window in config.get_mut_windows().iter_mut() {
+            window.set_size(width, height);
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_resolution(resolution: &str) -> Option<(u32, u32)> {
+    let mut parts = resolution.split('x');
+
+    match (parts.next().and_then(|w| w.parse().ok()), parts.next().and_then(|h| h.parse().ok()), parts.next()) {
+        (Some(width), Some(height), None) if width > 0 && height > 0 => Some((width, height)),
+        _ => None,
+    }
+}
@@ -1,7 +1,66 @@
 use specs::{VecStorage, Component};
 
 use math::{Point2, Vector3, Point3, Matrix4, OrthographicHelper};
-use utils::{GfxCoord};
+use math::prelude::{SquareMatrix, Transform};
+use utils::{GfxCoord, WindowId};
+
+#[derive(Debug, Clone)]
+pub struct Viewport {
+    x: GfxCoord,
+    y: GfxCoord,
+    width: GfxCoord,
+    height: GfxCoord,
+}
+
+impl Viewport {
+    pub fn new(x: GfxCoord, y: GfxCoord, width: GfxCoord, height: GfxCoord) -> Viewport {
+        Viewport {
+            x: x,
+            y: y,
+            width: width,
+            height: height,
+        }
+    }
+
+    pub fn new_full() -> Viewport {
+        Viewport::new(0.0, 0.0, 1.0, 1.0)
+    }
+
+    pub fn get_x(&self) -> GfxCoord {
+        self.x
+    }
+
+    pub fn get_y(&self) -> GfxCoord {
+        self.y
+    }
+
+    pub fn get_width(&self) -> GfxCoord {
+        self.width
+    }
+
+    pub fn get_height(&self) -> GfxCoord {
+        self.height
+    }
+
+    pub fn get_aspect_ratio(&self, window_width: u32, window_height: u32) -> GfxCoord {
+        (self.width * window_width as GfxCoord) / (self.height * window_height as GfxCoord)
+    }
+
+    pub fn contains_screen_point(&self, screen_point: Point2<GfxCoord>) -> bool {
+        let x = screen_point.x;
+        let y = 1.0 - screen_point.y;
+
+        x >= self.x && x <= self.x + self.width && y >= self.y && y <= self.y + self.height
+    }
+
+    pub fn build_matrix(&self) -> Matrix4<GfxCoord> {
+        Matrix4::from_translation(Vector3::new(
+            self.x * 2.0 + self.width - 1.0,
+            self.y * 2.0 + self.height - 1.0,
+            0.0
+        )) * Matrix4::from_nonuniform_scale(self.width, self.height, 1.0)
+    }
+}
 
 #[derive(Debug)]
 pub struct Camera {
@@ -9,7 +68,9 @@ pub struct Camera {
     target: Point3<GfxCoord>,
     up: Vector3<GfxCoord>,
     ortho_helper: OrthographicHelper,
-    is_main: bool,
+    window_id: WindowId,
+    viewport: Viewport,
+    order: u8,
     dirty_1: bool,
     dirty_2: bool,
 }
@@ -20,19 +81,31 @@ impl Camera {
         target: Point3<GfxCoord>,
         up: Vector3<GfxCoord>,
         ortho_helper: OrthographicHelper,
-        is_main: bool
+        window_id: WindowId
     ) -> Camera {
         Camera {
             eye: eye,
             target: target,
             up: up,
             ortho_helper: ortho_helper,
-            is_main: is_main,
+            window_id: window_id,
+            viewport: Viewport::new_full(),
+            order: 0,
             dirty_1: true,
             dirty_2: true,
         }
     }
 
+    pub fn with_viewport(mut self, viewport: Viewport) -> Camera {
+        self.viewport = viewport;
+        self
+    }
+
+    pub fn with_order(mut self, order: u8) -> Camera {
+        self.order = order;
+        self
+    }
+
     pub fn set_offset(&mut self, offset: Point2<GfxCoord>) {
         self.set_eye(Point3::new(offset.x, offset.y, 2.0));
         self.set_target(Point3::new(offset.x, offset.y, 0.0));
@@ -64,6 +137,16 @@ impl Camera {
         self.set_dirty();
     }
 
+    pub fn set_aspect_ratio(&mut self, aspect_ratio: GfxCoord) {
+        self.ortho_helper = self.ortho_helper.with_aspect_ratio(aspect_ratio);
+        self.set_dirty();
+    }
+
+    pub fn resize(&mut self, window_width: u32, window_height: u32) {
+        let aspect_ratio = self.viewport.get_aspect_ratio(window_width, window_height);
+        self.set_aspect_ratio(aspect_ratio);
+    }
+
     pub fn get_offset(&self) -> Point2<GfxCoord> {
         Point2::new(self.get_eye().x, self.get_eye().y)
     }
@@ -76,19 +159,51 @@ impl Camera {
         self.ortho_helper.build_matrix()
     }
 
-    pub fn is_main(&self) -> bool {
-        self.is_main
+    pub fn clone_window_id(&self) -> WindowId {
+        self.window_id.clone()
+    }
+
+    pub fn get_viewport(&self) -> &Viewport {
+        &self.viewport
+    }
+
+    pub fn get_order(&self) -> u8 {
+        self.order
+    }
+
+    pub fn get_viewport_proj(&self) -> Matrix4<GfxCoord> {
+        self.viewport.build_matrix() * self.get_proj()
+    }
+
+    pub fn get_view_proj(&self) -> Matrix4<GfxCoord> {
+        self.get_viewport_proj() * self.get_view()
     }
 
     pub fn screen_to_world_point(&self, screen_point: Point2<GfxCoord>) -> Point2<GfxCoord> {
-        let view_depth = self.ortho_helper.get_view_depth();
+        let ndc = Point3::new(screen_point.x * 2.0 - 1.0, 1.0 - screen_point.y * 2.0, 0.0);
 
-        let world_point = Point2::new(
-            (((screen_point.x * 2.0) - 1.0) * view_depth) * 4.0 / 5.0 + self.get_offset().x,
-            (((1.0 - screen_point.y) * 2.0 - 1.0) * view_depth / self.ortho_helper.get_aspect_ratio()) * 4.0 / 5.0 + self.get_offset().y
-        );
+        let world_point = match self.get_view_proj().invert() {
+            Some(inverse) => inverse.transform_point(ndc),
+            None => Point3::new(self.get_offset().x, self.get_offset().y, 0.0),
+        };
 
-        world_point
+        Point2::new(world_point.x, world_point.y)
+    }
+
+    pub fn world_to_screen_point(&self, world_point: Point2<GfxCoord>) -> Point2<GfxCoord> {
+        let ndc = self.get_view_proj().transform_point(Point3::new(world_point.x, world_point.y, 0.0));
+
+        Point2::new((ndc.x + 1.0) / 2.0, (1.0 - ndc.y) / 2.0)
+    }
+
+    pub fn pixel_to_world_point(&self, pixel: Point2<GfxCoord>, window_width: u32, window_height: u32) -> Point2<GfxCoord> {
+        self.screen_to_world_point(Point2::new(pixel.x / window_width as GfxCoord, pixel.y / window_height as GfxCoord))
+    }
+
+    pub fn world_to_pixel_point(&self, world_point: Point2<GfxCoord>, window_width: u32, window_height: u32) -> Point2<GfxCoord> {
+        let screen_point = self.world_to_screen_point(world_point);
+
+        Point2::new(screen_point.x * window_width as GfxCoord, screen_point.y * window_height as GfxCoord)
     }
 
     fn set_dirty(&mut self) {
@@ -110,3 +225,91 @@ impl Camera {
 impl Component for Camera {
     type Storage = VecStorage<Camera>;
 }
+
+#[cfg(test)]
+mod tests {
+    use math::{Point2, Point3, Vector3, OrthographicHelper};
+    use utils::{GfxCoord, WindowId};
+
+    use super::{Camera, Viewport};
+
+    const EPSILON: GfxCoord = 0.001;
+
+    fn make_camera(viewport: Viewport, window_width: u32, window_height: u32) -> Camera {
+        let mut camera = Camera::new(
+            Point3::new(0.0, 0.0, 2.0),
+            Point3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            OrthographicHelper::new(1.0, -10.0, 10.0, 0.0, 10.0),
+            WindowId(0)
+        ).with_viewport(viewport);
+
+        camera.resize(window_width, window_height);
+        camera
+    }
+
+    fn assert_close(a: Point2<GfxCoord>, b: Point2<GfxCoord>) {
+        assert!((a.x - b.x).abs() < EPSILON && (a.y - b.y).abs() < EPSILON, "{:?} != {:?}", a, b);
+    }
+
+    fn assert_screen_round_trip(camera: &Camera) {
+        for &(x, y) in &[(0.0, 0.0), (0.25, 0.75), (0.5, 0.5), (0.9, 0.1), (1.0, 1.0)] {
+            let screen_point = Point2::new(x, y);
+            let world_point = camera.screen_to_world_point(screen_point);
+            assert_close(camera.world_to_screen_point(world_point), screen_point);
+        }
+    }
+
+    fn assert_pixel_round_trip(camera: &Camera, window_width: u32, window_height: u32) {
+        for &(x, y) in &[(0.0, 0.0), (17.0, 230.0), (400.0, 300.0), (window_width as GfxCoord, window_height as GfxCoord)] {
+            let pixel = Point2::new(x, y);
+            let world_point = camera.pixel_to_world_point(pixel, window_width, window_height);
+            assert_close(camera.world_to_pixel_point(world_point, window_width, window_height), pixel);
+        }
+    }
+
+    #[test]
+    fn round_trip_full_viewport() {
+        let camera = make_camera(Viewport::new_full(), 640, 640);
+
+        assert_screen_round_trip(&camera);
+        assert_pixel_round_trip(&camera, 640, 640);
+    }
+
+    #[test]
+    fn round_trip_non_square_window() {
+        for &(width, height) in &[(800, 600), (600, 800), (1920, 400)] {
+            let camera = make_camera(Viewport::new_full(), width, height);
+
+            assert_screen_round_trip(&camera);
+            assert_pixel_round_trip(&camera, width, height);
+        }
+    }
+
+    #[test]
+    fn round_trip_sub_viewport() {
+        let camera = make_camera(Viewport::new(0.5, 0.25, 0.5, 0.5), 800, 600);
+
+        assert_screen_round_trip(&camera);
+        assert_pixel_round_trip(&camera, 800, 600);
+    }
+
+    #[test]
+    fn round_trip_translated_camera() {
+        let mut camera = make_camera(Viewport::new(0.0, 0.0, 0.5, 1.0), 1024, 768);
+        camera.set_offset(Point2::new(3.5, -7.25));
+
+        assert_screen_round_trip(&camera);
+        assert_pixel_round_trip(&camera, 1024, 768);
+    }
+
+    #[test]
+    fn viewport_center_is_camera_offset() {
+        let mut camera = make_camera(Viewport::new(0.5, 0.25, 0.5, 0.5), 800, 600);
+        camera.set_offset(Point2::new(3.5, -7.25));
+
+        // Viewport y runs bottom up, screen y runs top down
+        assert_close(camera.screen_to_world_point(Point2::new(0.75, 0.5)), Point2::new(3.5, -7.25));
+        assert_close(camera.world_to_screen_point(Point2::new(3.5, -7.25)), Point2::new(0.75, 0.5));
+    }
+}
@@ -8,11 +8,13 @@ extern crate utils;
 pub use dependencies::{specs};
 
 pub mod camera;
+pub mod material;
 pub mod render_data;
 pub mod render_id;
 pub mod transform;
 
-pub use ::camera::Camera;
+pub use ::camera::{Camera, Viewport};
+pub use ::material::{BlendMode, Material, Palette, BLEND_MODES, MATERIALS, PALETTE_SIZE};
 pub use ::render_data::RenderData;
 pub use ::render_id::RenderId;
 pub use ::transform::Transform;
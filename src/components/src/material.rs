@@ -0,0 +1,73 @@
+pub const PALETTE_SIZE: usize = 4;
+
+// Each entry is one color, matched and replaced by index
+pub type Palette = [[f32; 4]; PALETTE_SIZE];
+
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
+pub enum Material {
+    Flat,
+    Textured,
+    PaletteSwap,
+}
+
+pub const MATERIALS: &'static [Material] = &[
+    Material::Flat,
+    Material::Textured,
+    Material::PaletteSwap,
+];
+
+impl Material {
+    pub fn get_name(&self) -> &'static str {
+        match *self {
+            Material::Flat => "flat",
+            Material::Textured => "textured",
+            Material::PaletteSwap => "palette_swap",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Material> {
+        MATERIALS.iter().find(|material| material.get_name() == name).cloned()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
+pub enum BlendMode {
+    Alpha,
+    Additive,
+    Multiply,
+    PremultipliedAlpha,
+    Opaque,
+}
+
+pub const BLEND_MODES: &'static [BlendMode] = &[
+    BlendMode::Alpha,
+    BlendMode::Additive,
+    BlendMode::Multiply,
+    BlendMode::PremultipliedAlpha,
+    BlendMode::Opaque,
+];
+
+impl BlendMode {
+    pub fn get_name(&self) -> &'static str {
+        match *self {
+            BlendMode::Alpha => "alpha",
+            BlendMode::Additive => "additive",
+            BlendMode::Multiply => "multiply",
+            BlendMode::PremultipliedAlpha => "premultiplied_alpha",
+            BlendMode::Opaque => "opaque",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<BlendMode> {
+        BLEND_MODES.iter().find(|blend_mode| blend_mode.get_name() == name).cloned()
+    }
+
+    // Draw order inside a layer, opaque first so blended sprites land on top of it
+    pub fn get_order(&self) -> u8 {
+        match *self {
+            BlendMode::Opaque => 0,
+            BlendMode::Alpha | BlendMode::PremultipliedAlpha | BlendMode::Multiply => 1,
+            BlendMode::Additive => 2,
+        }
+    }
+}
@@ -1,6 +1,8 @@
 use specs::{Component, VecStorage};
 
-#[derive(Debug)]
+use material::{BlendMode, Material, Palette};
+
+#[derive(Debug, Clone)]
 pub struct RenderData {
     tint: [f32; 4],
     layer: u8,
@@ -8,6 +10,12 @@ pub struct RenderData {
     spritesheet_size: &'static [f32; 2],
     mirror_x: bool,
     mirror_y: bool,
+    batched: bool,
+    material: Material,
+    material_params: [f32; 4],
+    palette: Option<(&'static Palette, &'static Palette)>,
+    blend_mode: BlendMode,
+    depth_write: bool,
     dirty_1: bool,
     dirty_2: bool,
 }
@@ -21,6 +29,12 @@ impl RenderData {
             spritesheet_size: spritesheet_size,
             mirror_x: false,
             mirror_y: false,
+            batched: true,
+            material: Material::Textured,
+            material_params: [0.0; 4],
+            palette: None,
+            blend_mode: BlendMode::Alpha,
+            depth_write: true,
             dirty_1: true,
             dirty_2: true,
         }
@@ -57,6 +71,38 @@ impl RenderData {
         self.set_dirty();
     }
 
+    pub fn set_batched(&mut self, batched: bool) {
+        self.batched = batched;
+    }
+
+    pub fn set_material(&mut self, material: Material) {
+        self.material = material;
+        self.set_dirty();
+    }
+
+    pub fn set_material_params(&mut self, material_params: [f32; 4]) {
+        self.material_params = material_params;
+        self.set_dirty();
+    }
+
+    pub fn set_palette(&mut self, from: &'static Palette, to: &'static Palette) {
+        self.palette = Some((from, to));
+        self.set_dirty();
+    }
+
+    pub fn clear_palette(&mut self) {
+        self.palette = None;
+        self.set_dirty();
+    }
+
+    pub fn set_blend_mode(&mut self, blend_mode: BlendMode) {
+        self.blend_mode = blend_mode;
+    }
+
+    pub fn set_depth_write(&mut self, depth_write: bool) {
+        self.depth_write = depth_write;
+    }
+
     pub fn get_layer(&self) -> u8 {
         self.layer
     }
@@ -69,6 +115,35 @@ impl RenderData {
         self.mirror_y
     }
 
+    pub fn is_batched(&self) -> bool {
+        self.batched
+    }
+
+    pub fn get_material(&self) -> Material {
+        self.material
+    }
+
+    pub fn get_material_params(&self) -> [f32; 4] {
+        self.material_params
+    }
+
+    // The params alpha is how far the sprite flashes towards the params color
+    pub fn has_flash(&self) -> bool {
+        self.material_params[3] != 0.0
+    }
+
+    pub fn get_palette(&self) -> Option<(&'static Palette, &'static Palette)> {
+        self.palette
+    }
+
+    pub fn get_blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
+    pub fn get_depth_write(&self) -> bool {
+        self.depth_write
+    }
+
     pub fn get_tint(&self) -> [f32; 4] {
         self.tint.clone()
     }
@@ -1,12 +1,13 @@
 use specs::{VecStorage, Component};
 
-use math::{Matrix4, Point2, Vector3, Euler, Rad};
-use math::prelude::{Zero};
+use math::{Matrix4, Point2, Point3, Vector3, Euler, Rad};
+use math::prelude::{InnerSpace, SquareMatrix, Transform as TransformTrait, Zero};
 use utils::{GfxCoord, Coord};
 
 #[derive(Debug)]
 pub struct Transform {
     translation: Vector3<GfxCoord>,
+    previous_translation: Vector3<GfxCoord>,
     rotation: Euler<Rad<GfxCoord>>,
     scale: Vector3<GfxCoord>,
 }
@@ -15,6 +16,7 @@ impl Transform {
     pub fn new(pos: Vector3<GfxCoord>, rotation: Euler<Rad<GfxCoord>>, scale: Vector3<GfxCoord>) -> Transform {
         Transform {
             translation: pos,
+            previous_translation: pos,
             rotation: rotation,
             scale: scale,
         }
@@ -40,8 +42,31 @@ impl Transform {
         self.translation
     }
 
+    pub fn snapshot(&mut self) {
+        self.previous_translation = self.translation;
+    }
+
     pub fn get_model(&self) -> Matrix4<GfxCoord> {
-        Matrix4::from_translation(self.translation) * Matrix4::from(self.rotation) * Matrix4::from_nonuniform_scale(self.scale.x, self.scale.y, self.scale.z)
+        self.build_model(self.translation)
+    }
+
+    pub fn get_interpolated_model(&self, alpha: GfxCoord) -> Matrix4<GfxCoord> {
+        self.build_model(self.previous_translation.lerp(self.translation, alpha))
+    }
+
+    pub fn contains_point(&self, point: Point2<GfxCoord>) -> bool {
+        let inverse = match self.get_model().invert() {
+            Some(inverse) => inverse,
+            None => return false,
+        };
+
+        let local = inverse.transform_point(Point3::new(point.x, point.y, 0.0));
+
+        local.x >= 0.0 && local.x <= 1.0 && local.y >= 0.0 && local.y <= 1.0
+    }
+
+    fn build_model(&self, translation: Vector3<GfxCoord>) -> Matrix4<GfxCoord> {
+        Matrix4::from_translation(translation) * Matrix4::from(self.rotation) * Matrix4::from_nonuniform_scale(self.scale.x, self.scale.y, self.scale.z)
     }
 
     pub fn get_gui_offset(&self) -> Point2<Coord> {
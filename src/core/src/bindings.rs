@@ -0,0 +1,464 @@
+use std::collections::{HashMap};
+use std::error::{Error};
+use std::fmt;
+use std::fs::{File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use sdl2::keyboard::{Keycode, Mod, LCTRLMOD, RCTRLMOD, LSHIFTMOD, RSHIFTMOD, LALTMOD, RALTMOD};
+use sdl2::controller::{Axis, Button};
+use sdl2::mouse::{MouseButton as SdlMouseButton};
+use toml::{Parser, Table, Value};
+
+use systems::input::{Action, ACTIONS};
+
+pub const DEFAULT_BINDINGS_NAME: &'static str = "bindings.toml";
+
+const CONTROLLER_PREFIX: &'static str = "Pad:";
+
+#[derive(Debug)]
+pub enum BindingsError {
+    Io(PathBuf, io::Error),
+    Parse(PathBuf, String),
+    Binding(String, String),
+    Conflict(Binding, Action, Action),
+}
+
+impl fmt::Display for BindingsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BindingsError::Io(ref path, ref err) => write!(f, "unable to read bindings {:?}: {}", path, err),
+            BindingsError::Parse(ref path, ref desc) => write!(f, "unable to parse bindings {:?}: {}", path, desc),
+            BindingsError::Binding(ref field, ref reason) => write!(f, "invalid binding `{}`: {}", field, reason),
+            BindingsError::Conflict(ref binding, ref first, ref second) => write!(f, "`{}` is bound to both `{}` and `{}`", binding, first, second),
+        }
+    }
+}
+
+impl Error for BindingsError {
+    fn description(&self) -> &str {
+        match *self {
+            BindingsError::Io(..) => "bindings io error",
+            BindingsError::Parse(..) => "bindings parse error",
+            BindingsError::Binding(..) => "invalid binding",
+            BindingsError::Conflict(..) => "conflicting bindings",
+        }
+    }
+
+    fn cause(&self) -> Option<&Error> {
+        match *self {
+            BindingsError::Io(_, ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
+pub struct Modifiers {
+    ctrl: bool,
+    shift: bool,
+    alt: bool,
+}
+
+impl Modifiers {
+    pub fn new(ctrl: bool, shift: bool, alt: bool) -> Modifiers {
+        Modifiers {
+            ctrl: ctrl,
+            shift: shift,
+            alt: alt,
+        }
+    }
+
+    pub fn none() -> Modifiers {
+        Modifiers::new(false, false, false)
+    }
+
+    pub fn from_keymod(keymod: Mod) -> Modifiers {
+        Modifiers::new(
+            keymod.intersects(LCTRLMOD | RCTRLMOD),
+            keymod.intersects(LSHIFTMOD | RSHIFTMOD),
+            keymod.intersects(LALTMOD | RALTMOD)
+        )
+    }
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub enum Binding {
+    Key(Keycode, Modifiers),
+    Mouse(SdlMouseButton),
+    ControllerButton(Button),
+    ControllerAxis(Axis, bool),
+}
+
+impl Binding {
+    pub fn parse(text: &str) -> Option<Binding> {
+        if text.starts_with(CONTROLLER_PREFIX) {
+            return parse_controller(&text[CONTROLLER_PREFIX.len()..]);
+        }
+
+        let mut parts: Vec<&str> = text.split('+').map(|part| part.trim()).collect();
+        let key = match parts.pop() {
+            Some(key) if !key.is_empty() => key,
+            _ => return None,
+        };
+
+        let mut modifiers = Modifiers::none();
+
+        for part in parts {
+            match part.to_lowercase().as_str() {
+                "ctrl" => modifiers.ctrl = true,
+                "shift" => modifiers.shift = true,
+                "alt" => modifiers.alt = true,
+                _ => return None,
+            }
+        }
+
+        if let Some(mouse_btn) = parse_mouse_button(key) {
+            if modifiers != Modifiers::none() {
+                return None;
+            }
+            return Some(Binding::Mouse(mouse_btn));
+        }
+
+        Keycode::from_name(key).map(|keycode| Binding::Key(keycode, modifiers))
+    }
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Binding::Key(keycode, modifiers) => {
+                if modifiers.ctrl {
+                    try!(write!(f, "Ctrl+"));
+                }
+                if modifiers.shift {
+                    try!(write!(f, "Shift+"));
+                }
+                if modifiers.alt {
+                    try!(write!(f, "Alt+"));
+                }
+                write!(f, "{}", keycode.name())
+            },
+            Binding::Mouse(mouse_btn) => write!(f, "Mouse{:?}", mouse_btn),
+            Binding::ControllerButton(button) => write!(f, "{}{}", CONTROLLER_PREFIX, button.string()),
+            Binding::ControllerAxis(axis, positive) => write!(f, "{}{}{}", CONTROLLER_PREFIX, axis.string(), if positive { "+" } else { "-" }),
+        }
+    }
+}
+
+fn parse_controller(name: &str) -> Option<Binding> {
+    if name.ends_with('+') || name.ends_with('-') {
+        let positive = name.ends_with('+');
+        return Axis::from_string(&name[..name.len() - 1]).map(|axis| Binding::ControllerAxis(axis, positive));
+    }
+
+    Button::from_string(name).map(Binding::ControllerButton)
+}
+
+fn parse_mouse_button(key: &str) -> Option<SdlMouseButton> {
+    match key.to_lowercase().as_str() {
+        "mouseleft" => Some(SdlMouseButton::Left),
+        "mouseright" => Some(SdlMouseButton::Right),
+        "mousemiddle" => Some(SdlMouseButton::Middle),
+        "mousex1" => Some(SdlMouseButton::X1),
+        "mousex2" => Some(SdlMouseButton::X2),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Bindings {
+    path: Option<PathBuf>,
+    actions: HashMap<Binding, Action>,
+}
+
+impl Default for Bindings {
+    fn default() -> Bindings {
+        let defaults: &[(&str, Action)] = &[
+            ("Right", Action::MoveRight),
+            ("D", Action::MoveRight),
+            ("Left", Action::MoveLeft),
+            ("A", Action::MoveLeft),
+            ("Up", Action::MoveUp),
+            ("W", Action::MoveUp),
+            ("Down", Action::MoveDown),
+            ("S", Action::MoveDown),
+            ("Space", Action::Wait),
+            ("G", Action::PickUp),
+            ("I", Action::OpenInventory),
+            ("Pad:dpright", Action::MoveRight),
+            ("Pad:leftx+", Action::MoveRight),
+            ("Pad:dpleft", Action::MoveLeft),
+            ("Pad:leftx-", Action::MoveLeft),
+            ("Pad:dpup", Action::MoveUp),
+            ("Pad:lefty-", Action::MoveUp),
+            ("Pad:dpdown", Action::MoveDown),
+            ("Pad:lefty+", Action::MoveDown),
+            ("Pad:b", Action::Wait),
+            ("Pad:a", Action::PickUp),
+            ("Pad:y", Action::OpenInventory),
+            ("F5", Action::ReloadBindings),
+            ("`", Action::ToggleConsole),
+            ("Escape", Action::Quit),
+        ];
+
+        let mut actions = HashMap::new();
+
+        for &(text, action) in defaults {
+            if let Some(binding) = Binding::parse(text) {
+                actions.insert(binding, action);
+            }
+        }
+
+        Bindings {
+            path: None,
+            actions: actions,
+        }
+    }
+}
+
+impl Bindings {
+    pub fn load<P>(path: P) -> Result<Bindings, BindingsError>
+    where P: AsRef<Path> {
+        let path = path.as_ref();
+
+        let mut text = String::new();
+        try!(File::open(path).and_then(|mut file| file.read_to_string(&mut text)).map_err(|err| BindingsError::Io(path.to_path_buf(), err)));
+
+        let mut parser = Parser::new(&text);
+        let table = match parser.parse() {
+            Some(table) => table,
+            None => {
+                let desc = parser.errors.iter().map(|err| {
+                    let (line, col) = parser.to_linecol(err.lo);
+                    format!("{}:{}: {}", line + 1, col + 1, err.desc)
+                }).collect::<Vec<String>>().join(", ");
+                return Err(BindingsError::Parse(path.to_path_buf(), desc));
+            },
+        };
+
+        let mut bindings = try!(Bindings::from_table(&table));
+        bindings.path = Some(path.to_path_buf());
+        Ok(bindings)
+    }
+
+    pub fn load_or_default<P>(path: P) -> Result<Bindings, BindingsError>
+    where P: AsRef<Path> {
+        let path = path.as_ref();
+
+        if path.is_file() {
+            return Bindings::load(path);
+        }
+
+        warn!("No bindings found at {:?}, using defaults", path);
+        let mut bindings = Bindings::default();
+        bindings.path = Some(path.to_path_buf());
+        Ok(bindings)
+    }
+
+    pub fn from_table(table: &Table) -> Result<Bindings, BindingsError> {
+        let bindings = match table.get("bindings") {
+            Some(&Value::Table(ref bindings)) => bindings,
+            Some(other) => return Err(BindingsError::Binding("bindings".to_string(), format!("expected table, found {}", other.type_str()))),
+            None => return Err(BindingsError::Binding("bindings".to_string(), "missing table".to_string())),
+        };
+
+        let mut actions: HashMap<Binding, Action> = HashMap::new();
+
+        for (name, value) in bindings {
+            let field = format!("bindings.{}", name);
+
+            let action = try!(Action::from_name(name).ok_or_else(|| BindingsError::Binding(field.clone(), "unknown action".to_string())));
+
+            let values = match *value {
+                Value::Array(ref values) => values,
+                ref other => return Err(BindingsError::Binding(field, format!("expected array of strings, found {}", other.type_str()))),
+            };
+
+            for (index, value) in values.iter().enumerate() {
+                let text = match *value {
+                    Value::String(ref text) => text,
+                    ref other => return Err(BindingsError::Binding(format!("{}[{}]", field, index), format!("expected string, found {}", other.type_str()))),
+                };
+
+                let binding = try!(Binding::parse(text).ok_or_else(|| BindingsError::Binding(format!("{}[{}]", field, index), format!("unknown key or button {:?}", text))));
+
+                if let Some(&existing) = actions.get(&binding) {
+                    if existing != action {
+                        return Err(BindingsError::Conflict(binding, existing, action));
+                    }
+                }
+
+                actions.insert(binding, action);
+            }
+        }
+
+        for action in ACTIONS {
+            if !actions.values().any(|bound| bound == action) {
+                warn!("No binding for action: {}", action);
+            }
+        }
+
+        Ok(Bindings {
+            path: None,
+            actions: actions,
+        })
+    }
+
+    pub fn reload(&mut self) -> Result<(), BindingsError> {
+        let path = match self.path {
+            Some(ref path) => path.clone(),
+            None => return Ok(()),
+        };
+
+        *self = try!(Bindings::load_or_default(path));
+        Ok(())
+    }
+
+    pub fn get_action(&self, binding: &Binding) -> Option<Action> {
+        self.actions.get(binding).cloned()
+    }
+
+    pub fn get_key_action(&self, keycode: Keycode, keymod: Mod) -> Option<Action> {
+        let modifiers = Modifiers::from_keymod(keymod);
+
+        self.get_action(&Binding::Key(keycode, modifiers))
+            .or_else(|| self.get_action(&Binding::Key(keycode, Modifiers::none())))
+    }
+
+    pub fn get_key_actions(&self, keycode: Keycode) -> Vec<Action> {
+        let mut actions = vec!();
+
+        for (binding, action) in &self.actions {
+            if let Binding::Key(bound_keycode, _) = *binding {
+                if bound_keycode == keycode && !actions.contains(action) {
+                    actions.push(*action);
+                }
+            }
+        }
+
+        actions
+    }
+
+    pub fn get_mouse_action(&self, mouse_btn: SdlMouseButton) -> Option<Action> {
+        self.get_action(&Binding::Mouse(mouse_btn))
+    }
+
+    pub fn get_controller_button_action(&self, button: Button) -> Option<Action> {
+        self.get_action(&Binding::ControllerButton(button))
+    }
+
+    pub fn get_controller_axis_action(&self, axis: Axis, positive: bool) -> Option<Action> {
+        self.get_action(&Binding::ControllerAxis(axis, positive))
+    }
+
+    pub fn get_bindings(&self, action: Action) -> Vec<&Binding> {
+        let mut bindings: Vec<&Binding> = self.actions.iter().filter(|&(_, bound)| *bound == action).map(|(binding, _)| binding).collect();
+        bindings.sort_by_key(|binding| binding.to_string());
+        bindings
+    }
+
+    pub fn describe(&self, action: Action) -> String {
+        self.get_bindings(action).iter().map(|binding| binding.to_string()).collect::<Vec<String>>().join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sdl2::controller::{Axis, Button};
+    use sdl2::keyboard::{Keycode, LCTRLMOD, NOMOD};
+    use sdl2::mouse::{MouseButton as SdlMouseButton};
+    use toml::{Parser};
+
+    use systems::input::{Action};
+
+    use super::{Binding, Bindings, BindingsError, Modifiers};
+
+    fn parse(text: &str) -> Result<Bindings, BindingsError> {
+        let table = Parser::new(text).parse().expect("test bindings should be valid toml");
+        Bindings::from_table(&table)
+    }
+
+    #[test]
+    fn parses_keys_with_modifiers() {
+        assert_eq!(Binding::parse("A"), Some(Binding::Key(Keycode::A, Modifiers::none())));
+        assert_eq!(Binding::parse("Ctrl+Shift+A"), Some(Binding::Key(Keycode::A, Modifiers::new(true, true, false))));
+        assert_eq!(Binding::parse("alt + F5"), Some(Binding::Key(Keycode::F5, Modifiers::new(false, false, true))));
+        assert_eq!(Binding::parse("Hyper+A"), None);
+        assert_eq!(Binding::parse("Ctrl+"), None);
+    }
+
+    #[test]
+    fn parses_mouse_and_controller() {
+        assert_eq!(Binding::parse("MouseLeft"), Some(Binding::Mouse(SdlMouseButton::Left)));
+        assert_eq!(Binding::parse("Ctrl+MouseLeft"), None);
+        assert_eq!(Binding::parse("Pad:a"), Some(Binding::ControllerButton(Button::A)));
+        assert_eq!(Binding::parse("Pad:leftx-"), Some(Binding::ControllerAxis(Axis::LeftX, false)));
+        assert_eq!(Binding::parse("Pad:nothing"), None);
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for text in &["Ctrl+Alt+Q", "MouseRight", "Pad:b", "Pad:lefty+"] {
+            let binding = Binding::parse(text).unwrap();
+            assert_eq!(Binding::parse(&binding.to_string()), Some(binding));
+        }
+    }
+
+    #[test]
+    fn detects_conflicts() {
+        match parse("[bindings]\nwait = [\"Space\"]\npick_up = [\"Space\"]") {
+            Err(BindingsError::Conflict(binding, first, second)) => {
+                assert_eq!(binding, Binding::Key(Keycode::Space, Modifiers::none()));
+                assert!((first, second) == (Action::Wait, Action::PickUp) || (first, second) == (Action::PickUp, Action::Wait));
+            },
+            other => panic!("expected a conflict, found {:?}", other),
+        }
+    }
+
+    #[test]
+    fn allows_same_binding_twice_for_one_action() {
+        assert!(parse("[bindings]\nwait = [\"Space\", \"Space\"]").is_ok());
+    }
+
+    #[test]
+    fn modifiers_keep_bindings_apart() {
+        let bindings = parse("[bindings]\nwait = [\"S\"]\nquit = [\"Ctrl+S\"]").unwrap();
+
+        assert_eq!(bindings.get_key_action(Keycode::S, NOMOD), Some(Action::Wait));
+        assert_eq!(bindings.get_key_action(Keycode::S, LCTRLMOD), Some(Action::Quit));
+    }
+
+    #[test]
+    fn modified_press_falls_back_to_plain_binding() {
+        let bindings = parse("[bindings]\nwait = [\"Space\"]").unwrap();
+
+        assert_eq!(bindings.get_key_action(Keycode::Space, LCTRLMOD), Some(Action::Wait));
+    }
+
+    #[test]
+    fn rejects_unknown_actions_and_keys() {
+        match parse("[bindings]\njump = [\"Space\"]") {
+            Err(BindingsError::Binding(field, _)) => assert_eq!(field, "bindings.jump"),
+            other => panic!("expected a binding error, found {:?}", other),
+        }
+
+        match parse("[bindings]\nwait = [\"Space\", \"NotAKey\"]") {
+            Err(BindingsError::Binding(field, _)) => assert_eq!(field, "bindings.wait[1]"),
+            other => panic!("expected a binding error, found {:?}", other),
+        }
+    }
+
+    #[test]
+    fn describes_bindings_for_display() {
+        let bindings = parse("[bindings]\nmove_up = [\"W\", \"Up\"]").unwrap();
+
+        assert_eq!(bindings.describe(Action::MoveUp), "Up, W");
+        assert_eq!(bindings.describe(Action::Quit), "");
+    }
+
+    #[test]
+    fn default_bindings_cover_quit() {
+        assert_eq!(Bindings::default().get_key_action(Keycode::Escape, NOMOD), Some(Action::Quit));
+    }
+}
@@ -0,0 +1,675 @@
+use std::error::{Error};
+use std::fmt;
+use std::fs::{File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use find_folder::{Search};
+use toml::{Parser, Table, Value};
+
+use math::{OrthographicHelper};
+
+pub const DEFAULT_CONFIG_NAME: &'static str = "config.toml";
+
+const LOG_LEVELS: &'static [&'static str] = &["off", "error", "warn", "info", "debug", "trace"];
+
+const ROOT_KEYS: &'static [&'static str] = &["log_level", "ticks_per_second", "paths", "projection", "controller", "windows"];
+const PATHS_KEYS: &'static [&'static str] = &["assets", "shaders", "bindings"];
+const PROJECTION_KEYS: &'static [&'static str] = &["left", "right", "near", "far"];
+const CONTROLLER_KEYS: &'static [&'static str] = &["deadzone", "trigger_threshold"];
+const WINDOW_KEYS: &'static [&'static str] = &["title", "width", "height", "clear_color", "global_shortcuts", "cameras"];
+const CAMERA_KEYS: &'static [&'static str] = &["viewport", "order", "zoom"];
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(PathBuf, io::Error),
+    Parse(PathBuf, String),
+    Field(String, String),
+    Folder(String, String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ConfigError::Io(ref path, ref err) => write!(f, "unable to read config {:?}: {}", path, err),
+            ConfigError::Parse(ref path, ref desc) => write!(f, "unable to parse config {:?}: {}", path, desc),
+            ConfigError::Field(ref field, ref reason) => write!(f, "invalid config field `{}`: {}", field, reason),
+            ConfigError::Folder(ref field, ref reason) => write!(f, "unable to find folder for `{}`: {}", field, reason),
+        }
+    }
+}
+
+impl Error for ConfigError {
+    fn description(&self) -> &str {
+        match *self {
+            ConfigError::Io(..) => "config io error",
+            ConfigError::Parse(..) => "config parse error",
+            ConfigError::Field(..) => "invalid config field",
+            ConfigError::Folder(..) => "config folder not found",
+        }
+    }
+
+    fn cause(&self) -> Option<&Error> {
+        match *self {
+            ConfigError::Io(_, ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CameraConfig {
+    viewport: [f32; 4],
+    order: u8,
+    zoom: f32,
+}
+
+impl CameraConfig {
+    pub fn new(viewport: [f32; 4], order: u8, zoom: f32) -> CameraConfig {
+        CameraConfig {
+            viewport: viewport,
+            order: order,
+            zoom: zoom,
+        }
+    }
+
+    pub fn new_full() -> CameraConfig {
+        CameraConfig::new([0.0, 0.0, 1.0, 1.0], 0, 1.0)
+    }
+
+    pub fn get_viewport(&self) -> [f32; 4] {
+        self.viewport
+    }
+
+    pub fn get_order(&self) -> u8 {
+        self.order
+    }
+
+    pub fn get_zoom(&self) -> f32 {
+        self.zoom
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WindowConfig {
+    title: String,
+    width: u32,
+    height: u32,
+    clear_color: [f32; 4],
+    global_shortcuts: bool,
+    cameras: Vec<CameraConfig>,
+}
+
+impl WindowConfig {
+    pub fn new(title: String, width: u32, height: u32, clear_color: [f32; 4]) -> WindowConfig {
+        WindowConfig {
+            title: title,
+            width: width,
+            height: height,
+            clear_color: clear_color,
+            global_shortcuts: false,
+            cameras: vec!(CameraConfig::new_full()),
+        }
+    }
+
+    pub fn with_global_shortcuts(mut self, global_shortcuts: bool) -> WindowConfig {
+        self.global_shortcuts = global_shortcuts;
+        self
+    }
+
+    pub fn with_cameras(mut self, cameras: Vec<CameraConfig>) -> WindowConfig {
+        self.cameras = cameras;
+        self
+    }
+
+    pub fn get_title(&self) -> &str {
+        self.title.as_str()
+    }
+
+    pub fn get_width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn get_height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn get_clear_color(&self) -> [f32; 4] {
+        self.clear_color
+    }
+
+    pub fn get_global_shortcuts(&self) -> bool {
+        self.global_shortcuts
+    }
+
+    pub fn get_cameras(&self) -> &[CameraConfig] {
+        self.cameras.as_slice()
+    }
+
+    pub fn set_size(&mut self, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ProjectionConfig {
+    left: f32,
+    right: f32,
+    near: f32,
+    far: f32,
+}
+
+impl ProjectionConfig {
+    pub fn new(left: f32, right: f32, near: f32, far: f32) -> ProjectionConfig {
+        ProjectionConfig {
+            left: left,
+            right: right,
+            near: near,
+            far: far,
+        }
+    }
+
+    pub fn with_zoom(&self, zoom: f32) -> ProjectionConfig {
+        ProjectionConfig::new(self.left * zoom, self.right * zoom, self.near, self.far)
+    }
+
+    pub fn build_ortho_helper(&self, width: u32, height: u32) -> OrthographicHelper {
+        let aspect_ratio = width as f32 / height as f32;
+
+        OrthographicHelper::new(aspect_ratio, self.left, self.right, self.near, self.far)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    windows: Vec<WindowConfig>,
+    projection: ProjectionConfig,
+    assets_dir: String,
+    shaders_dir: String,
+    bindings_file: String,
+    log_level: String,
+    ticks_per_second: u32,
+    controller_deadzone: i16,
+    controller_trigger_threshold: i16,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            windows: vec!(
+                WindowConfig::new("First Window".to_string(), 640, 480, [1.0, 0.0, 0.0, 1.0]),
+                WindowConfig::new("Second Window".to_string(), 640, 480, [0.0, 0.0, 1.0, 1.0]).with_cameras(vec!(CameraConfig::new([0.0, 0.0, 1.0, 1.0], 0, 2.0)))
+            ),
+            projection: ProjectionConfig::new(-10.0, 10.0, 0.0, 10.0),
+            assets_dir: "assets".to_string(),
+            shaders_dir: "shaders".to_string(),
+            bindings_file: "bindings.toml".to_string(),
+            log_level: "warn".to_string(),
+            ticks_per_second: 60,
+            controller_deadzone: 8000,
+            controller_trigger_threshold: 16000,
+        }
+    }
+}
+
+impl Config {
+    pub fn load<P>(path: P) -> Result<Config, ConfigError>
+    where P: AsRef<Path> {
+        let path = path.as_ref();
+
+        let mut text = String::new();
+        try!(File::open(path).and_then(|mut file| file.read_to_string(&mut text)).map_err(|err| ConfigError::Io(path.to_path_buf(), err)));
+
+        let mut parser = Parser::new(&text);
+        let table = match parser.parse() {
+            Some(table) => table,
+            None => {
+                let desc = parser.errors.iter().map(|err| {
+                    let (line, col) = parser.to_linecol(err.lo);
+                    format!("{}:{}: {}", line + 1, col + 1, err.desc)
+                }).collect::<Vec<String>>().join(", ");
+                return Err(ConfigError::Parse(path.to_path_buf(), desc));
+            },
+        };
+
+        Config::from_table(&table)
+    }
+
+    pub fn load_default() -> Result<Config, ConfigError> {
+        let mut dir = PathBuf::from(".");
+
+        for _ in 0..4 {
+            let path = dir.join(DEFAULT_CONFIG_NAME);
+            if path.is_file() {
+                return Config::load(path);
+            }
+            dir.push("..");
+        }
+
+        Ok(Config::default())
+    }
+
+    pub fn from_table(table: &Table) -> Result<Config, ConfigError> {
+        let default = Config::default();
+
+        try!(check_keys(table, "", ROOT_KEYS));
+
+        let log_level = try!(get_string(table, "", "log_level", &default.log_level));
+        if !LOG_LEVELS.contains(&log_level.as_str()) {
+            return Err(ConfigError::Field("log_level".to_string(), format!("expected one of {:?}, found {:?}", LOG_LEVELS, log_level)));
+        }
+
+        let ticks_per_second = try!(get_u32(table, "", "ticks_per_second", default.ticks_per_second));
+
+        let empty = Table::new();
+
+        let paths = try!(get_table(table, "", "paths")).unwrap_or(&empty);
+        try!(check_keys(paths, "paths.", PATHS_KEYS));
+        let assets_dir = try!(get_string(paths, "paths.", "assets", &default.assets_dir));
+        let shaders_dir = try!(get_string(paths, "paths.", "shaders", &default.shaders_dir));
+        let bindings_file = try!(get_string(paths, "paths.", "bindings", &default.bindings_file));
+
+        let projection = {
+            let projection = try!(get_table(table, "", "projection")).unwrap_or(&empty);
+            try!(check_keys(projection, "projection.", PROJECTION_KEYS));
+            let left = try!(get_f32(projection, "projection.", "left", default.projection.left));
+            let right = try!(get_f32(projection, "projection.", "right", default.projection.right));
+            let near = try!(get_f32(projection, "projection.", "near", default.projection.near));
+            let far = try!(get_f32(projection, "projection.", "far", default.projection.far));
+
+            if left >= right {
+                return Err(ConfigError::Field("projection.right".to_string(), format!("must be greater than projection.left ({})", left)));
+            }
+            if near >= far {
+                return Err(ConfigError::Field("projection.far".to_string(), format!("must be greater than projection.near ({})", near)));
+            }
+
+            ProjectionConfig::new(left, right, near, far)
+        };
+
+        let controller = try!(get_table(table, "", "controller")).unwrap_or(&empty);
+        try!(check_keys(controller, "controller.", CONTROLLER_KEYS));
+        let controller_deadzone = try!(get_axis(controller, "controller.", "deadzone", default.controller_deadzone));
+        let controller_trigger_threshold = try!(get_axis(controller, "controller.", "trigger_threshold", default.controller_trigger_threshold));
+
+        let windows = match table.get("windows") {
+            None => default.windows.clone(),
+            Some(&Value::Array(ref windows)) => {
+                let mut out = vec!();
+                for (index, window) in windows.iter().enumerate() {
+                    let prefix = format!("windows[{}].", index);
+                    let window = match *window {
+                        Value::Table(ref window) => window,
+                        ref other => return Err(ConfigError::Field(format!("windows[{}]", index), format!("expected table, found {}", other.type_str()))),
+                    };
+                    try!(check_keys(window, &prefix, WINDOW_KEYS));
+                    let default_window = default.windows.get(index).unwrap_or(&default.windows[0]);
+                    out.push(WindowConfig::new(
+                        try!(get_string(window, &prefix, "title", &default_window.title)),
+                        try!(get_u32(window, &prefix, "width", default_window.width)),
+                        try!(get_u32(window, &prefix, "height", default_window.height)),
+                        try!(get_color(window, &prefix, "clear_color", default_window.clear_color))
+                    ).with_global_shortcuts(
+                        try!(get_bool(window, &prefix, "global_shortcuts", default_window.global_shortcuts))
+                    ).with_cameras(
+                        try!(get_cameras(window, &prefix, &default_window.cameras))
+                    ));
+                }
+                out
+            },
+            Some(other) => return Err(ConfigError::Field("windows".to_string(), format!("expected array of tables, found {}", other.type_str()))),
+        };
+
+        if windows.is_empty() {
+            return Err(ConfigError::Field("windows".to_string(), "at least one window is required".to_string()));
+        }
+
+        Ok(Config {
+            windows: windows,
+            projection: projection,
+            assets_dir: assets_dir,
+            shaders_dir: shaders_dir,
+            bindings_file: bindings_file,
+            log_level: log_level,
+            ticks_per_second: ticks_per_second,
+            controller_deadzone: controller_deadzone,
+            controller_trigger_threshold: controller_trigger_threshold,
+        })
+    }
+
+    pub fn get_windows(&self) -> &[WindowConfig] {
+        self.windows.as_slice()
+    }
+
+    pub fn get_mut_windows(&mut self) -> &mut Vec<WindowConfig> {
+        &mut self.windows
+    }
+
+    pub fn get_projection(&self) -> &ProjectionConfig {
+        &self.projection
+    }
+
+    pub fn get_bindings_file(&self) -> &str {
+        self.bindings_file.as_str()
+    }
+
+    pub fn get_log_level(&self) -> &str {
+        self.log_level.as_str()
+    }
+
+    pub fn get_ticks_per_second(&self) -> u32 {
+        self.ticks_per_second
+    }
+
+    pub fn set_ticks_per_second(&mut self, ticks_per_second: u32) {
+        self.ticks_per_second = ticks_per_second;
+    }
+
+    pub fn get_controller_deadzone(&self) -> i16 {
+        self.controller_deadzone
+    }
+
+    pub fn get_controller_trigger_threshold(&self) -> i16 {
+        self.controller_trigger_threshold
+    }
+
+    pub fn find_assets_dir(&self) -> Result<PathBuf, ConfigError> {
+        find_dir("paths.assets", &self.assets_dir)
+    }
+
+    pub fn find_shaders_dir(&self) -> Result<PathBuf, ConfigError> {
+        find_dir("paths.shaders", &self.shaders_dir)
+    }
+}
+
+fn find_dir(field: &str, dir: &str) -> Result<PathBuf, ConfigError> {
+    let path = PathBuf::from(dir);
+    if path.is_dir() {
+        return Ok(path);
+    }
+
+    Search::ParentsThenKids(3, 3).for_folder(dir).map_err(|err| ConfigError::Folder(field.to_string(), format!("{:?}: {}", dir, err)))
+}
+
+fn check_keys(table: &Table, prefix: &str, allowed: &[&str]) -> Result<(), ConfigError> {
+    match table.keys().find(|key| !allowed.contains(&key.as_str())) {
+        Some(key) => Err(ConfigError::Field(format!("{}{}", prefix, key), format!("unknown key, expected one of {:?}", allowed))),
+        None => Ok(()),
+    }
+}
+
+fn get_table<'a>(table: &'a Table, prefix: &str, key: &str) -> Result<Option<&'a Table>, ConfigError> {
+    match table.get(key) {
+        None => Ok(None),
+        Some(&Value::Table(ref table)) => Ok(Some(table)),
+        Some(other) => Err(ConfigError::Field(format!("{}{}", prefix, key), format!("expected table, found {}", other.type_str()))),
+    }
+}
+
+fn get_string(table: &Table, prefix: &str, key: &str, default: &str) -> Result<String, ConfigError> {
+    match table.get(key) {
+        None => Ok(default.to_string()),
+        Some(&Value::String(ref value)) => Ok(value.clone()),
+        Some(other) => Err(ConfigError::Field(format!("{}{}", prefix, key), format!("expected string, found {}", other.type_str()))),
+    }
+}
+
+fn get_u32(table: &Table, prefix: &str, key: &str, default: u32) -> Result<u32, ConfigError> {
+    match table.get(key) {
+        None => Ok(default),
+        Some(&Value::Integer(value)) if value > 0 && value <= u32::max_value() as i64 => Ok(value as u32),
+        Some(&Value::Integer(value)) => Err(ConfigError::Field(format!("{}{}", prefix, key), format!("expected a positive integer, found {}", value))),
+        Some(other) => Err(ConfigError::Field(format!("{}{}", prefix, key), format!("expected integer, found {}", other.type_str()))),
+    }
+}
+
+fn get_bool(table: &Table, prefix: &str, key: &str, default: bool) -> Result<bool, ConfigError> {
+    match table.get(key) {
+        None => Ok(default),
+        Some(&Value::Boolean(value)) => Ok(value),
+        Some(other) => Err(ConfigError::Field(format!("{}{}", prefix, key), format!("expected boolean, found {}", other.type_str()))),
+    }
+}
+
+fn get_axis(table: &Table, prefix: &str, key: &str, default: i16) -> Result<i16, ConfigError> {
+    let value = try!(get_u32(table, prefix, key, default as u32));
+
+    if value > i16::max_value() as u32 {
+        return Err(ConfigError::Field(format!("{}{}", prefix, key), format!("expected a value no greater than {}, found {}", i16::max_value(), value)));
+    }
+
+    Ok(value as i16)
+}
+
+fn get_f32(table: &Table, prefix: &str, key: &str, default: f32) -> Result<f32, ConfigError> {
+    match table.get(key) {
+        None => Ok(default),
+        Some(&Value::Float(value)) => Ok(value as f32),
+        Some(&Value::Integer(value)) => Ok(value as f32),
+        Some(other) => Err(ConfigError::Field(format!("{}{}", prefix, key), format!("expected float, found {}", other.type_str()))),
+    }
+}
+
+fn get_cameras(table: &Table, prefix: &str, default: &[CameraConfig]) -> Result<Vec<CameraConfig>, ConfigError> {
+    let cameras = match table.get("cameras") {
+        None => return Ok(default.to_vec()),
+        Some(&Value::Array(ref cameras)) => cameras,
+        Some(other) => return Err(ConfigError::Field(format!("{}cameras", prefix), format!("expected array of tables, found {}", other.type_str()))),
+    };
+
+    let mut out = vec!();
+
+    for (index, camera) in cameras.iter().enumerate() {
+        let camera_prefix = format!("{}cameras[{}].", prefix, index);
+        let camera = match *camera {
+            Value::Table(ref camera) => camera,
+            ref other => return Err(ConfigError::Field(format!("{}cameras[{}]", prefix, index), format!("expected table, found {}", other.type_str()))),
+        };
+        try!(check_keys(camera, &camera_prefix, CAMERA_KEYS));
+
+        let viewport = try!(get_viewport(camera, &camera_prefix, "viewport", [0.0, 0.0, 1.0, 1.0]));
+
+        let order = match camera.get("order") {
+            None => 0,
+            Some(&Value::Integer(order)) if order >= 0 && order <= u8::max_value() as i64 => order as u8,
+            Some(&Value::Integer(order)) => return Err(ConfigError::Field(format!("{}order", camera_prefix), format!("expected an integer from 0 to {}, found {}", u8::max_value(), order))),
+            Some(other) => return Err(ConfigError::Field(format!("{}order", camera_prefix), format!("expected integer, found {}", other.type_str()))),
+        };
+
+        let zoom = try!(get_f32(camera, &camera_prefix, "zoom", 1.0));
+        if !(zoom > 0.0 && zoom.is_finite()) {
+            return Err(ConfigError::Field(format!("{}zoom", camera_prefix), format!("expected a positive number, found {}", zoom)));
+        }
+
+        out.push(CameraConfig::new(viewport, order, zoom));
+    }
+
+    if out.is_empty() {
+        return Err(ConfigError::Field(format!("{}cameras", prefix), "at least one camera is required".to_string()));
+    }
+
+    Ok(out)
+}
+
+fn get_color(table: &Table, prefix: &str, key: &str, default: [f32; 4]) -> Result<[f32; 4], ConfigError> {
+    let field = format!("{}{}", prefix, key);
+
+    let values = match table.get(key) {
+        None => return Ok(default),
+        Some(&Value::Array(ref values)) => values,
+        Some(other) => return Err(ConfigError::Field(field, format!("expected array of 4 floats, found {}", other.type_str()))),
+    };
+
+    if values.len() != 4 {
+        return Err(ConfigError::Field(field, format!("expected 4 components, found {}", values.len())));
+    }
+
+    let mut color = [0.0; 4];
+
+    for (index, value) in values.iter().enumerate() {
+        let component = match *value {
+            Value::Float(value) => value as f32,
+            Value::Integer(value) => value as f32,
+            ref other => return Err(ConfigError::Field(format!("{}[{}]", field, index), format!("expected float, found {}", other.type_str()))),
+        };
+
+        if component < 0.0 || component > 1.0 {
+            return Err(ConfigError::Field(format!("{}[{}]", field, index), format!("expected a value between 0.0 and 1.0, found {}", component)));
+        }
+
+        color[index] = component;
+    }
+
+    Ok(color)
+}
+
+// Viewports are x, y, width, height as fractions of the window
+fn get_viewport(table: &Table, prefix: &str, key: &str, default: [f32; 4]) -> Result<[f32; 4], ConfigError> {
+    let field = format!("{}{}", prefix, key);
+
+    let values = match table.get(key) {
+        None => return Ok(default),
+        Some(&Value::Array(ref values)) => values,
+        Some(other) => return Err(ConfigError::Field(field, format!("expected [x, y, width, height], found {}", other.type_str()))),
+    };
+
+    if values.len() != 4 {
+        return Err(ConfigError::Field(field, format!("expected x, y, width and height, found {} values", values.len())));
+    }
+
+    let mut viewport = [0.0; 4];
+
+    for (index, value) in values.iter().enumerate() {
+        viewport[index] = match *value {
+            Value::Float(value) => value as f32,
+            Value::Integer(value) => value as f32,
+            ref other => return Err(ConfigError::Field(format!("{}[{}]", field, index), format!("expected a fraction of the window, found {}", other.type_str()))),
+        };
+    }
+
+    let (x, y, width, height) = (viewport[0], viewport[1], viewport[2], viewport[3]);
+
+    if x < 0.0 || y < 0.0 || width <= 0.0 || height <= 0.0 || x + width > 1.0 || y + height > 1.0 {
+        return Err(ConfigError::Field(field, format!("expected a non empty rect inside the window, found x {}, y {}, width {}, height {}", x, y, width, height)));
+    }
+
+    Ok(viewport)
+}
+
+#[cfg(test)]
+mod tests {
+    use toml::{Parser};
+
+    use super::{Config, ConfigError};
+
+    fn parse(text: &str) -> Result<Config, ConfigError> {
+        let table = Parser::new(text).parse().expect("test config should be valid toml");
+        Config::from_table(&table)
+    }
+
+    fn field_of(result: Result<Config, ConfigError>) -> String {
+        match result {
+            Err(ConfigError::Field(field, _)) => field,
+            other => panic!("expected a field error, found {:?}", other),
+        }
+    }
+
+    #[test]
+    fn empty_config_uses_defaults() {
+        let config = parse("").unwrap();
+        let default = Config::default();
+
+        assert_eq!(config.get_ticks_per_second(), default.get_ticks_per_second());
+        assert_eq!(config.get_log_level(), default.get_log_level());
+        assert_eq!(config.get_windows().len(), default.get_windows().len());
+    }
+
+    #[test]
+    fn reads_known_keys() {
+        let config = parse("
+            ticks_per_second = 30
+            [controller]
+            deadzone = 100
+            [[windows]]
+            title = \"Only\"
+            width = 320
+            height = 200
+            clear_color = [0.0, 0.5, 1.0, 1.0]
+        ").unwrap();
+
+        assert_eq!(config.get_ticks_per_second(), 30);
+        assert_eq!(config.get_controller_deadzone(), 100);
+        assert_eq!(config.get_windows().len(), 1);
+        assert_eq!(config.get_windows()[0].get_title(), "Only");
+        assert_eq!(config.get_windows()[0].get_width(), 320);
+        assert_eq!(config.get_windows()[0].get_clear_color(), [0.0, 0.5, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn rejects_unknown_root_key() {
+        assert_eq!(field_of(parse("tick_per_second = 60")), "tick_per_second");
+    }
+
+    #[test]
+    fn rejects_unknown_keys_in_tables() {
+        assert_eq!(field_of(parse("[paths]\nasset = \"assets\"")), "paths.asset");
+        assert_eq!(field_of(parse("[projection]\nlft = 1.0")), "projection.lft");
+        assert_eq!(field_of(parse("[controller]\ndead_zone = 1")), "controller.dead_zone");
+        assert_eq!(field_of(parse("[[windows]]\ntitle = \"a\"\n[[windows]]\nwidht = 10")), "windows[1].widht");
+    }
+
+    #[test]
+    fn rejects_bad_values() {
+        assert_eq!(field_of(parse("log_level = \"loud\"")), "log_level");
+        assert_eq!(field_of(parse("ticks_per_second = 0")), "ticks_per_second");
+        assert_eq!(field_of(parse("[projection]\nleft = 1.0\nright = -1.0")), "projection.right");
+        assert_eq!(field_of(parse("[controller]\ndeadzone = 40000")), "controller.deadzone");
+        assert_eq!(field_of(parse("[[windows]]\nclear_color = [1.0, 0.0]")), "windows[0].clear_color");
+        assert_eq!(field_of(parse("[[windows]]\nclear_color = [2.0, 0.0, 0.0, 1.0]")), "windows[0].clear_color[0]");
+        assert_eq!(field_of(parse("windows = []")), "windows");
+    }
+
+    #[test]
+    fn reads_cameras() {
+        let config = parse("
+            [[windows]]
+            [[windows.cameras]]
+            [[windows.cameras]]
+            viewport = [0.75, 0.75, 0.25, 0.25]
+            order = 1
+            zoom = 4.0
+        ").unwrap();
+
+        let cameras = config.get_windows()[0].get_cameras();
+        assert_eq!(cameras.len(), 2);
+        assert_eq!(cameras[0].get_viewport(), [0.0, 0.0, 1.0, 1.0]);
+        assert_eq!(cameras[1].get_viewport(), [0.75, 0.75, 0.25, 0.25]);
+        assert_eq!(cameras[1].get_order(), 1);
+        assert_eq!(cameras[1].get_zoom(), 4.0);
+    }
+
+    #[test]
+    fn rejects_bad_cameras() {
+        assert_eq!(field_of(parse("[[windows]]\ncameras = []")), "windows[0].cameras");
+        assert_eq!(field_of(parse("[[windows]]\n[[windows.cameras]]\nzoom = 0.0")), "windows[0].cameras[0].zoom");
+        assert_eq!(field_of(parse("[[windows]]\n[[windows.cameras]]\norder = 300")), "windows[0].cameras[0].order");
+        assert_eq!(field_of(parse("[[windows]]\n[[windows.cameras]]\nviewport = [0.5, 0.0, 0.75, 1.0]")), "windows[0].cameras[0].viewport");
+        assert_eq!(field_of(parse("[[windows]]\n[[windows.cameras]]\nzom = 2.0")), "windows[0].cameras[0].zom");
+    }
+
+    #[test]
+    fn rejects_bad_viewports() {
+        for viewport in &["[0.0, 0.0, 1.0]", "[-0.25, 0.0, 0.5, 0.5]", "[0.0, 0.0, 0.0, 1.0]", "[0.5, 0.5, 0.5, 0.75]", "0.5"] {
+            let text = format!("[[windows]]\n[[windows.cameras]]\nviewport = {}", viewport);
+            assert_eq!(field_of(parse(&text)), "windows[0].cameras[0].viewport");
+        }
+
+        assert_eq!(field_of(parse("[[windows]]\n[[windows.cameras]]\nviewport = [\"a\", \"b\", \"c\", \"d\"]")), "windows[0].cameras[0].viewport[0]");
+
+        match parse("[[windows]]\n[[windows.cameras]]\nviewport = [0.5, 0.0, 0.75, 1.0]") {
+            Err(ConfigError::Field(_, desc)) => assert!(desc.contains("rect inside the window"), "unexpected message: {}", desc),
+            other => panic!("expected a field error, found {:?}", other),
+        }
+    }
+}
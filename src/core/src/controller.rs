@@ -0,0 +1,362 @@
+use std::collections::{HashMap, VecDeque};
+
+use sdl2::{GameControllerSubsystem};
+use sdl2::controller::{Axis, Button, GameController};
+use sdl2::event::{Event};
+
+use systems::input::{Action};
+
+use ::{Bindings, CoreError};
+
+pub type ControllerId = i32;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ControllerEvent {
+    Connected(ControllerId),
+    Disconnected(ControllerId),
+    Button(ControllerId, Button, bool),
+    Axis(ControllerId, Axis, i16),
+}
+
+pub trait ControllerSource {
+    fn poll_event(&mut self) -> Option<ControllerEvent>;
+}
+
+#[derive(Debug)]
+pub struct SyntheticControllerSource {
+    events: VecDeque<ControllerEvent>,
+}
+
+impl SyntheticControllerSource {
+    pub fn new() -> SyntheticControllerSource {
+        SyntheticControllerSource {
+            events: VecDeque::new(),
+        }
+    }
+
+    pub fn push(&mut self, event: ControllerEvent) {
+        self.events.push_back(event);
+    }
+}
+
+impl ControllerSource for SyntheticControllerSource {
+    fn poll_event(&mut self) -> Option<ControllerEvent> {
+        self.events.pop_front()
+    }
+}
+
+pub struct SdlControllerSource {
+    subsystem: GameControllerSubsystem,
+    controllers: HashMap<ControllerId, GameController>,
+    events: VecDeque<ControllerEvent>,
+}
+
+impl SdlControllerSource {
+    pub fn new(subsystem: GameControllerSubsystem) -> SdlControllerSource {
+        SdlControllerSource {
+            subsystem: subsystem,
+            controllers: HashMap::new(),
+            events: VecDeque::new(),
+        }
+    }
+
+    pub fn handle_sdl_event(&mut self, event: &Event) -> Result<bool, CoreError> {
+        match *event {
+            Event::ControllerDeviceAdded { which, .. } => {
+                // One bad device shouldn't take the game down with it
+                let controller = match self.subsystem.open(which as u32) {
+                    Ok(controller) => controller,
+                    Err(err) => {
+                        warn!("Skipping Controller {}: {:?}", which, err);
+                        return Ok(true);
+                    },
+                };
+                let id = controller.instance_id();
+                warn!("Controller Connected: {} ({})", controller.name(), id);
+                self.controllers.insert(id, controller);
+                self.events.push_back(ControllerEvent::Connected(id));
+            },
+            Event::ControllerDeviceRemoved { which, .. } => {
+                warn!("Controller Disconnected: {}", which);
+                self.controllers.remove(&which);
+                self.events.push_back(ControllerEvent::Disconnected(which));
+            },
+            Event::ControllerButtonDown { which, button, .. } => {
+                self.events.push_back(ControllerEvent::Button(which, button, true));
+            },
+            Event::ControllerButtonUp { which, button, .. } => {
+                self.events.push_back(ControllerEvent::Button(which, button, false));
+            },
+            Event::ControllerAxisMotion { which, axis, value, .. } => {
+                self.events.push_back(ControllerEvent::Axis(which, axis, value));
+            },
+            _ => return Ok(false),
+        }
+
+        Ok(true)
+    }
+}
+
+impl ControllerSource for SdlControllerSource {
+    fn poll_event(&mut self) -> Option<ControllerEvent> {
+        self.events.pop_front()
+    }
+}
+
+#[derive(Debug)]
+pub struct ControllerMapper {
+    deadzone: i16,
+    trigger_threshold: i16,
+    // Each held source remembers the action it pressed, so a rebind can't strand it
+    held_buttons: HashMap<(ControllerId, Button), Option<Action>>,
+    active_axes: HashMap<(ControllerId, Axis, bool), Option<Action>>,
+    // Every controller feeds one InputState, an action stays held until its last source lets go
+    held_actions: HashMap<Action, u32>,
+}
+
+impl ControllerMapper {
+    pub fn new(deadzone: i16, trigger_threshold: i16) -> ControllerMapper {
+        ControllerMapper {
+            deadzone: deadzone,
+            trigger_threshold: trigger_threshold,
+            held_buttons: HashMap::new(),
+            active_axes: HashMap::new(),
+            held_actions: HashMap::new(),
+        }
+    }
+
+    pub fn map_event(&mut self, bindings: &Bindings, event: ControllerEvent) -> Vec<(Action, bool)> {
+        let mut actions = vec!();
+
+        match event {
+            ControllerEvent::Connected(_) => (),
+            ControllerEvent::Disconnected(id) => {
+                let buttons: Vec<(ControllerId, Button)> = self.held_buttons.keys().filter(|&&(held_id, _)| held_id == id).cloned().collect();
+                for button in buttons {
+                    if let Some(action) = self.held_buttons.remove(&button) {
+                        self.release(action, &mut actions);
+                    }
+                }
+
+                let axes: Vec<(ControllerId, Axis, bool)> = self.active_axes.keys().filter(|&&(active_id, _, _)| active_id == id).cloned().collect();
+                for axis in axes {
+                    if let Some(action) = self.active_axes.remove(&axis) {
+                        self.release(action, &mut actions);
+                    }
+                }
+            },
+            ControllerEvent::Button(id, button, pressed) => {
+                if pressed {
+                    if !self.held_buttons.contains_key(&(id, button)) {
+                        let action = bindings.get_controller_button_action(button);
+                        self.held_buttons.insert((id, button), action);
+                        self.press(action, &mut actions);
+                    }
+                } else if let Some(action) = self.held_buttons.remove(&(id, button)) {
+                    self.release(action, &mut actions);
+                }
+            },
+            ControllerEvent::Axis(id, axis, value) => {
+                let threshold = match axis {
+                    Axis::TriggerLeft | Axis::TriggerRight => self.trigger_threshold,
+                    _ => self.deadzone,
+                };
+
+                for &positive in &[true, false] {
+                    let active = if positive {
+                        value > threshold
+                    } else {
+                        (value as i32) < -(threshold as i32)
+                    };
+
+                    if active {
+                        if !self.active_axes.contains_key(&(id, axis, positive)) {
+                            let action = bindings.get_controller_axis_action(axis, positive);
+                            self.active_axes.insert((id, axis, positive), action);
+                            self.press(action, &mut actions);
+                        }
+                    } else if let Some(action) = self.active_axes.remove(&(id, axis, positive)) {
+                        self.release(action, &mut actions);
+                    }
+                }
+            },
+        }
+
+        actions
+    }
+
+    fn press(&mut self, action: Option<Action>, actions: &mut Vec<(Action, bool)>) {
+        if let Some(action) = action {
+            let count = self.held_actions.entry(action).or_insert(0);
+            *count += 1;
+            if *count == 1 {
+                actions.push((action, true));
+            }
+        }
+    }
+
+    fn release(&mut self, action: Option<Action>, actions: &mut Vec<(Action, bool)>) {
+        if let Some(action) = action {
+            let released = match self.held_actions.get_mut(&action) {
+                Some(count) => {
+                    *count -= 1;
+                    *count == 0
+                },
+                None => false,
+            };
+
+            if released {
+                self.held_actions.remove(&action);
+                actions.push((action, false));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sdl2::controller::{Axis, Button};
+    use toml::{Parser};
+
+    use systems::input::{Action};
+
+    use ::{Bindings};
+    use super::{ControllerEvent, ControllerMapper, ControllerSource, SyntheticControllerSource};
+
+    const DEADZONE: i16 = 8000;
+    const TRIGGER_THRESHOLD: i16 = 16000;
+
+    fn make_bindings() -> Bindings {
+        let table = Parser::new(
+            "[bindings]\nmove_right = [\"Pad:leftx+\"]\nmove_left = [\"Pad:leftx-\"]\nwait = [\"Pad:b\"]\npick_up = [\"Pad:righttrigger+\"]"
+        ).parse().expect("test bindings should be valid toml");
+        Bindings::from_table(&table).unwrap()
+    }
+
+    fn run(events: Vec<ControllerEvent>) -> Vec<(Action, bool)> {
+        let bindings = make_bindings();
+        let mut source = SyntheticControllerSource::new();
+        let mut mapper = ControllerMapper::new(DEADZONE, TRIGGER_THRESHOLD);
+
+        for event in events {
+            source.push(event);
+        }
+
+        let mut actions = vec!();
+        while let Some(event) = source.poll_event() {
+            actions.extend(mapper.map_event(&bindings, event));
+        }
+        actions
+    }
+
+    #[test]
+    fn stick_inside_deadzone_is_ignored() {
+        assert_eq!(run(vec!(
+            ControllerEvent::Axis(0, Axis::LeftX, DEADZONE),
+            ControllerEvent::Axis(0, Axis::LeftX, -DEADZONE),
+            ControllerEvent::Axis(0, Axis::LeftX, 0),
+        )), vec!());
+    }
+
+    #[test]
+    fn stick_outside_deadzone_presses_and_releases() {
+        assert_eq!(run(vec!(
+            ControllerEvent::Axis(0, Axis::LeftX, DEADZONE + 1),
+            ControllerEvent::Axis(0, Axis::LeftX, 20000),
+            ControllerEvent::Axis(0, Axis::LeftX, -20000),
+            ControllerEvent::Axis(0, Axis::LeftX, 0),
+        )), vec!(
+            (Action::MoveRight, true),
+            (Action::MoveRight, false),
+            (Action::MoveLeft, true),
+            (Action::MoveLeft, false),
+        ));
+    }
+
+    #[test]
+    fn trigger_uses_its_own_threshold() {
+        assert_eq!(run(vec!(
+            ControllerEvent::Axis(0, Axis::TriggerRight, DEADZONE + 1),
+            ControllerEvent::Axis(0, Axis::TriggerRight, TRIGGER_THRESHOLD),
+        )), vec!());
+
+        assert_eq!(run(vec!(
+            ControllerEvent::Axis(0, Axis::TriggerRight, TRIGGER_THRESHOLD + 1),
+            ControllerEvent::Axis(0, Axis::TriggerRight, 0),
+        )), vec!(
+            (Action::PickUp, true),
+            (Action::PickUp, false),
+        ));
+    }
+
+    #[test]
+    fn repeated_button_down_is_one_press() {
+        assert_eq!(run(vec!(
+            ControllerEvent::Button(0, Button::B, true),
+            ControllerEvent::Button(0, Button::B, true),
+            ControllerEvent::Button(0, Button::B, false),
+            ControllerEvent::Button(0, Button::B, false),
+        )), vec!(
+            (Action::Wait, true),
+            (Action::Wait, false),
+        ));
+    }
+
+    #[test]
+    fn disconnect_releases_held_actions() {
+        let mut actions = run(vec!(
+            ControllerEvent::Connected(0),
+            ControllerEvent::Button(0, Button::B, true),
+            ControllerEvent::Axis(0, Axis::LeftX, 20000),
+            ControllerEvent::Disconnected(0),
+        ));
+
+        let released = actions.split_off(2);
+        assert_eq!(actions, vec!((Action::Wait, true), (Action::MoveRight, true)));
+        assert_eq!(released.len(), 2);
+        assert!(released.contains(&(Action::Wait, false)));
+        assert!(released.contains(&(Action::MoveRight, false)));
+    }
+
+    #[test]
+    fn disconnect_keeps_actions_another_controller_holds() {
+        assert_eq!(run(vec!(
+            ControllerEvent::Connected(0),
+            ControllerEvent::Connected(1),
+            ControllerEvent::Button(0, Button::B, true),
+            ControllerEvent::Axis(0, Axis::LeftX, 20000),
+            ControllerEvent::Button(1, Button::B, true),
+            ControllerEvent::Disconnected(0),
+        )), vec!(
+            (Action::Wait, true),
+            (Action::MoveRight, true),
+            (Action::MoveRight, false),
+        ));
+    }
+
+    #[test]
+    fn shared_action_is_released_by_its_last_source() {
+        assert_eq!(run(vec!(
+            ControllerEvent::Button(0, Button::B, true),
+            ControllerEvent::Button(1, Button::B, true),
+            ControllerEvent::Button(0, Button::B, false),
+            ControllerEvent::Button(1, Button::B, false),
+        )), vec!(
+            (Action::Wait, true),
+            (Action::Wait, false),
+        ));
+    }
+
+    #[test]
+    fn disconnect_without_held_actions_releases_nothing() {
+        assert_eq!(run(vec!(
+            ControllerEvent::Connected(0),
+            ControllerEvent::Button(0, Button::B, true),
+            ControllerEvent::Button(0, Button::B, false),
+            ControllerEvent::Disconnected(0),
+        )), vec!(
+            (Action::Wait, true),
+            (Action::Wait, false),
+        ));
+    }
+}
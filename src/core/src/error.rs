@@ -0,0 +1,92 @@
+use std::error::{Error};
+use std::fmt;
+
+use event::{EventError};
+use graphics::{GraphicsError};
+use systems::replay::{ReplayError};
+
+use bindings::{BindingsError};
+use config::{ConfigError};
+
+#[derive(Debug)]
+pub enum CoreError {
+    Config(ConfigError),
+    Bindings(BindingsError),
+    Graphics(GraphicsError),
+    Event(EventError),
+    Replay(ReplayError),
+    Sdl(String),
+    GameThread(String),
+    Unsupported(String),
+}
+
+impl fmt::Display for CoreError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CoreError::Config(ref err) => write!(f, "config error: {}", err),
+            CoreError::Bindings(ref err) => write!(f, "bindings error: {}", err),
+            CoreError::Graphics(ref err) => write!(f, "graphics error: {}", err),
+            CoreError::Event(ref err) => write!(f, "event error: {}", err),
+            CoreError::Replay(ref err) => write!(f, "replay error: {}", err),
+            CoreError::Sdl(ref err) => write!(f, "sdl error: {}", err),
+            CoreError::GameThread(ref err) => write!(f, "game thread panicked: {}", err),
+            CoreError::Unsupported(ref err) => write!(f, "unsupported: {}", err),
+        }
+    }
+}
+
+impl Error for CoreError {
+    fn description(&self) -> &str {
+        match *self {
+            CoreError::Config(ref err) => err.description(),
+            CoreError::Bindings(ref err) => err.description(),
+            CoreError::Graphics(ref err) => err.description(),
+            CoreError::Event(ref err) => err.description(),
+            CoreError::Replay(ref err) => err.description(),
+            CoreError::Sdl(..) => "sdl error",
+            CoreError::GameThread(..) => "game thread panicked",
+            CoreError::Unsupported(..) => "unsupported",
+        }
+    }
+
+    fn cause(&self) -> Option<&Error> {
+        match *self {
+            CoreError::Config(ref err) => Some(err),
+            CoreError::Bindings(ref err) => Some(err),
+            CoreError::Graphics(ref err) => Some(err),
+            CoreError::Event(ref err) => Some(err),
+            CoreError::Replay(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<ConfigError> for CoreError {
+    fn from(err: ConfigError) -> CoreError {
+        CoreError::Config(err)
+    }
+}
+
+impl From<BindingsError> for CoreError {
+    fn from(err: BindingsError) -> CoreError {
+        CoreError::Bindings(err)
+    }
+}
+
+impl From<GraphicsError> for CoreError {
+    fn from(err: GraphicsError) -> CoreError {
+        CoreError::Graphics(err)
+    }
+}
+
+impl From<EventError> for CoreError {
+    fn from(err: EventError) -> CoreError {
+        CoreError::Event(err)
+    }
+}
+
+impl From<ReplayError> for CoreError {
+    fn from(err: ReplayError) -> CoreError {
+        CoreError::Replay(err)
+    }
+}
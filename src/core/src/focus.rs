@@ -0,0 +1,132 @@
+use std::collections::{HashSet};
+
+use systems::input::{Action};
+use utils::{WindowId};
+
+// Only these reach windows with global shortcuts, everything else stays in the window it came from
+const SHORTCUT_ACTIONS: &'static [Action] = &[Action::Quit, Action::ToggleConsole, Action::ReloadBindings];
+
+#[derive(Debug)]
+pub struct FocusState {
+    focused: Option<WindowId>,
+    global_shortcuts: HashSet<WindowId>,
+}
+
+impl FocusState {
+    pub fn new() -> FocusState {
+        FocusState {
+            focused: None,
+            global_shortcuts: HashSet::new(),
+        }
+    }
+
+    pub fn focus_gained(&mut self, window_id: WindowId) {
+        self.focused = Some(window_id);
+    }
+
+    pub fn focus_lost(&mut self, window_id: &WindowId) -> bool {
+        if self.focused.as_ref() == Some(window_id) {
+            self.focused = None;
+            return true;
+        }
+
+        false
+    }
+
+    pub fn get_focused(&self) -> Option<&WindowId> {
+        self.focused.as_ref()
+    }
+
+    pub fn accepts(&self, window_id: &WindowId) -> bool {
+        self.focused.as_ref().map_or(true, |focused| focused == window_id)
+    }
+
+    pub fn set_global_shortcuts(&mut self, window_id: WindowId, global_shortcuts: bool) {
+        if global_shortcuts {
+            self.global_shortcuts.insert(window_id);
+        } else {
+            self.global_shortcuts.remove(&window_id);
+        }
+    }
+
+    pub fn get_targets(&self, window_id: &WindowId, action: Action) -> Vec<WindowId> {
+        if !SHORTCUT_ACTIONS.contains(&action) {
+            return vec!(window_id.clone());
+        }
+
+        let mut targets: Vec<WindowId> = self.global_shortcuts.iter().filter(|global| *global != window_id).cloned().collect();
+        targets.push(window_id.clone());
+        targets.sort();
+        targets
+    }
+
+    pub fn remove_window(&mut self, window_id: &WindowId) {
+        self.focus_lost(window_id);
+        self.global_shortcuts.remove(window_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use systems::input::{Action};
+    use utils::{WindowId};
+
+    use super::{FocusState};
+
+    #[test]
+    fn targets_are_sorted_and_unique() {
+        let mut focus = FocusState::new();
+        for id in &[7, 2, 5, 3] {
+            focus.set_global_shortcuts(WindowId(*id), true);
+        }
+
+        assert_eq!(focus.get_targets(&WindowId(5), Action::Quit), vec!(WindowId(2), WindowId(3), WindowId(5), WindowId(7)));
+        assert_eq!(focus.get_targets(&WindowId(4), Action::ToggleConsole), vec!(WindowId(2), WindowId(3), WindowId(4), WindowId(5), WindowId(7)));
+    }
+
+    #[test]
+    fn movement_is_not_broadcast() {
+        let mut focus = FocusState::new();
+        focus.set_global_shortcuts(WindowId(1), true);
+
+        for action in &[Action::MoveRight, Action::MoveLeft, Action::MoveUp, Action::MoveDown, Action::Wait] {
+            assert_eq!(focus.get_targets(&WindowId(2), *action), vec!(WindowId(2)));
+        }
+        assert_eq!(focus.get_targets(&WindowId(2), Action::ReloadBindings), vec!(WindowId(1), WindowId(2)));
+    }
+
+    #[test]
+    fn targets_without_global_shortcuts() {
+        let mut focus = FocusState::new();
+        focus.set_global_shortcuts(WindowId(1), true);
+        focus.set_global_shortcuts(WindowId(1), false);
+
+        assert_eq!(focus.get_targets(&WindowId(3), Action::Quit), vec!(WindowId(3)));
+    }
+
+    #[test]
+    fn focus_follows_gain_and_loss() {
+        let mut focus = FocusState::new();
+        assert!(focus.accepts(&WindowId(1)));
+
+        focus.focus_gained(WindowId(1));
+        assert!(focus.accepts(&WindowId(1)));
+        assert!(!focus.accepts(&WindowId(2)));
+
+        assert!(!focus.focus_lost(&WindowId(2)));
+        assert!(focus.focus_lost(&WindowId(1)));
+        assert_eq!(focus.get_focused(), None);
+    }
+
+    #[test]
+    fn removing_a_window_drops_its_focus_and_shortcuts() {
+        let mut focus = FocusState::new();
+        focus.focus_gained(WindowId(1));
+        focus.set_global_shortcuts(WindowId(1), true);
+
+        focus.remove_window(&WindowId(1));
+
+        assert_eq!(focus.get_focused(), None);
+        assert_eq!(focus.get_targets(&WindowId(2), Action::Quit), vec!(WindowId(2)));
+    }
+}
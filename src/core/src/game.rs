@@ -1,31 +1,70 @@
 use art::{layers, tiles};
-use components::{Camera, RenderData, RenderId, Transform};
+use components::{Camera, RenderData, RenderId, Transform, Viewport};
 pub use dependencies::{specs, time};
-use event::{BackChannel};
+use event::{BackChannel, EventBus};
 use math::{OrthographicHelper, Point3, Vector3};
-use systems::render::{RenderSystem, RenderSystemSend, WindowedToRender, WindowedFromRender};
-use systems::control::{ControlSystem, WindowedToControl, WindowedFromControl};
-use utils::{Delta, FpsCounter};
+use systems::console::{Console, ConsoleSystem, LayerVisibility, TimeScale};
+use systems::control::{ControlSystem, QuitFlag, WindowRequests, WindowedToControl, WindowedFromControl};
+use systems::input::{InputState};
+use systems::movement::{MovementSystem};
+use systems::replay::{InputLog};
+use systems::picking::{Picking, PickingSystem};
+use systems::rng::{GameRng, Seed};
+use utils::{Delta, FixedTimestep, FpsCounter, Step, TickNum, WindowId};
 
-use self::specs::{World, Planner};
+use self::specs::{World, Planner, System};
 use self::time::{precise_time_ns};
 use ::{RenderIds};
+use config::{ProjectionConfig, WindowConfig};
+
+const MAX_TICKS_PER_FRAME: u32 = 8;
+
+pub fn make_camera(window_id: WindowId, ortho_helper: OrthographicHelper) -> Camera {
+    Camera::new(
+        Point3::new(0.0, 0.0, 2.0),
+        Point3::new(0.0, 0.0, 0.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        ortho_helper,
+        window_id
+    )
+}
+
+pub fn make_cameras(window_id: WindowId, window_config: &WindowConfig, projection: &ProjectionConfig) -> Vec<Camera> {
+    window_config.get_cameras().iter().map(|camera_config| {
+        let viewport = camera_config.get_viewport();
+
+        let mut camera = make_camera(
+            window_id.clone(),
+            projection.with_zoom(camera_config.get_zoom()).build_ortho_helper(window_config.get_width(), window_config.get_height())
+        ).with_viewport(Viewport::new(viewport[0], viewport[1], viewport[2], viewport[3])).with_order(camera_config.get_order());
+
+        camera.resize(window_config.get_width(), window_config.get_height());
+        camera
+    }).collect()
+}
 
 pub struct Game {
-    planner: Planner<Delta>,
+    planner: Planner<Step>,
+    event_bus: EventBus,
     last_time: u64,
+    timestep: FixedTimestep,
     fps_counter: FpsCounter,
 }
 
 impl Game {
-    pub fn new(
+    pub fn new<R>(
         render_ids: RenderIds,
-        render_system_send: RenderSystemSend,
-        render_back_channel: BackChannel<WindowedToRender, WindowedFromRender>,
+        renderer: R,
         control_back_channel: BackChannel<WindowedToControl, WindowedFromControl>,
-        ortho_helper: OrthographicHelper,
-    ) -> Game {
+        input_log: InputLog,
+        cameras: Vec<Camera>,
+        ticks_per_second: u32,
+        seed: Seed,
+    ) -> Game
+    where R: System<Step> + 'static {
         warn!("Starting New Game");
+        let event_bus = EventBus::new();
+
         let mut planner = {
             let mut world = World::new();
 
@@ -34,22 +73,25 @@ impl Game {
             world.register::<RenderId>();
             world.register::<Transform>();
 
-            Planner::<Delta>::new(world, 8)
-        };
+            world.add_resource(QuitFlag::new());
+            world.add_resource(WindowRequests::new());
+            world.add_resource(InputState::new());
+            world.add_resource(Picking::new());
+            world.add_resource(Console::new());
+            world.add_resource(TimeScale::new());
+            world.add_resource(LayerVisibility::new());
+            world.add_resource(GameRng::new(seed));
+            world.add_resource(event_bus.clone());
 
-        warn!("Creating Render System");
-        let renderer = RenderSystem::new(render_back_channel, render_system_send);
+            Planner::<Step>::new(world, 8)
+        };
 
-        warn!("Creating Camera");
-        planner.mut_world().create_now()
-            .with(Camera::new(
-                Point3::new(0.0, 0.0, 2.0),
-                Point3::new(0.0, 0.0, 0.0),
-                Vector3::new(0.0, 1.0, 0.0),
-                ortho_helper,
-                true
-            ))
-            .build();
+        for camera in cameras {
+            warn!("Creating Camera for: {:?}", camera.clone_window_id());
+            planner.mut_world().create_now()
+                .with(camera)
+                .build();
+        }
 
         for (_, render_id) in &render_ids {
             warn!("Render Id: {:?}", render_id);
@@ -62,11 +104,32 @@ impl Game {
 
         warn!("Adding Control System");
         planner.add_system(
-            ControlSystem::new(control_back_channel),
+            ControlSystem::new(control_back_channel, input_log),
             "control",
             30
         );
 
+        warn!("Adding Console System");
+        planner.add_system(
+            ConsoleSystem::new(),
+            "console",
+            27
+        );
+
+        warn!("Adding Picking System");
+        planner.add_system(
+            PickingSystem::new(event_bus.publisher()),
+            "picking",
+            25
+        );
+
+        warn!("Adding Movement System");
+        planner.add_system(
+            MovementSystem::new(),
+            "movement",
+            20
+        );
+
         warn!("Adding Render System");
         planner.add_system(
             renderer,
@@ -77,7 +140,9 @@ impl Game {
         warn!("Creating Game Struct");
         Game {
             planner: planner,
+            event_bus: event_bus,
             last_time: precise_time_ns(),
+            timestep: FixedTimestep::new(ticks_per_second, MAX_TICKS_PER_FRAME),
             fps_counter: FpsCounter::new(),
         }
     }
@@ -87,9 +152,73 @@ impl Game {
         let delta = (new_time - self.last_time) as Delta / 1e9;
         self.last_time = new_time;
 
-        self.planner.dispatch(delta);
+        self.advance(delta)
+    }
+
+    pub fn advance(&mut self, delta: Delta) -> bool {
+        let time_scale = self.get_time_scale();
+        self.timestep.accumulate(delta * time_scale);
+
+        while let Some(tick) = self.timestep.take_tick() {
+            self.snapshot_transforms();
+            // Systems are idle after the snapshot, deliver what the previous tick published
+            self.event_bus.flush();
+            self.planner.dispatch(Step::Tick(tick, self.timestep.get_tick_length()));
+        }
+
+        self.planner.dispatch(Step::Render(self.timestep.get_alpha()));
         self.fps_counter.frame(delta);
 
-        true
+        !self.is_quitting()
+    }
+
+    pub fn get_event_bus(&self) -> &EventBus {
+        &self.event_bus
+    }
+
+    pub fn get_tick_length(&self) -> Delta {
+        self.timestep.get_tick_length()
+    }
+
+    pub fn get_ticks(&self) -> TickNum {
+        self.timestep.get_ticks()
+    }
+
+    pub fn get_world(&mut self) -> &World {
+        self.planner.wait();
+        self.planner.mut_world()
+    }
+
+    pub fn get_mut_world(&mut self) -> &mut World {
+        self.planner.wait();
+        self.planner.mut_world()
+    }
+
+    pub fn shutdown(mut self) -> TickNum {
+        warn!("Shutting Down Game");
+        self.planner.wait();
+        self.timestep.get_ticks()
+    }
+
+    fn get_time_scale(&mut self) -> Delta {
+        self.planner.wait();
+        self.planner.mut_world().read_resource::<TimeScale>().get_scale()
+    }
+
+    fn is_quitting(&mut self) -> bool {
+        self.planner.wait();
+        self.planner.mut_world().read_resource::<QuitFlag>().is_set()
+    }
+
+    fn snapshot_transforms(&mut self) {
+        use self::specs::Join;
+
+        self.planner.wait();
+
+        let mut transforms = self.planner.mut_world().write::<Transform>();
+
+        for transform in (&mut transforms).iter() {
+            transform.snapshot();
+        }
     }
 }
@@ -2,20 +2,44 @@
 // use glutin::Event::{MouseMoved, KeyboardInput, Closed, MouseInput, Resized};
 // use glutin::ElementState::{Pressed, Released};
 
+use std::collections::{HashMap};
+
+use glutin::{MouseButton};
 use sdl2::keyboard::{Keycode};
+use sdl2::mouse::{MouseButton as SdlMouseButton};
 use sdl2::{EventPump};
 
 use event::{FrontChannel};
+use graphics::rl_sdl2::{SdlGraphic};
+use systems::console::{ConsoleKey};
 use systems::control::{WindowedToControl, WindowedFromControl, ToControl, FromControl};
+use systems::input::{Action};
 use systems::render::{WindowedToRender, WindowedFromRender, ToRender, FromRender};
+use utils::{WindowId};
+
+use controller::{ControllerMapper, ControllerSource, SdlControllerSource};
+use focus::{FocusState};
+use ::{Bindings, CoreError, WindowManager};
 
 // use ::render_thread::{FromRenderThread, ToRenderThread};
 
-pub fn handle_events(event_pump: &mut EventPump, render_back_channel: &mut FrontChannel<WindowedToRender, WindowedFromRender>, control_back_channel: &mut FrontChannel<WindowedToControl, WindowedFromControl>) -> bool {
+pub fn handle_events(event_pump: &mut EventPump, window_manager: &WindowManager, bindings: &mut Bindings, controllers: &mut SdlControllerSource, focus: &mut FocusState, console_window: &mut Option<WindowId>, sdl_graphics: &mut HashMap<WindowId, SdlGraphic>, render_back_channel: &mut FrontChannel<WindowedToRender, WindowedFromRender>, control_back_channel: &mut FrontChannel<WindowedToControl, WindowedFromControl>) -> Result<bool, CoreError> {
+    let mut console_opened = false;
+
     for event in event_pump.poll_iter() {
         use sdl2::event::Event;
         use sdl2::event::{WindowEventId};
+
+        if try!(controllers.handle_sdl_event(&event)) {
+            continue;
+        }
+
         match event {
+            Event::Quit {
+                ..
+            } => {
+                return Ok(true);
+            },
             Event::Window {
                 timestamp,
                 window_id,
@@ -25,7 +49,35 @@ pub fn handle_events(event_pump: &mut EventPump, render_back_channel: &mut Front
             } => {
                 match win_event_id {
                     WindowEventId::Close => {
-                        return true;
+                        if console_window.as_ref() == Some(&WindowId(window_id)) {
+                            window_manager.stop_text_input();
+                            *console_window = None;
+                        }
+
+                        focus.remove_window(&WindowId(window_id));
+
+                        if try!(window_manager.close(WindowId(window_id), sdl_graphics, render_back_channel, control_back_channel)) {
+                            return Ok(true);
+                        }
+                    },
+                    WindowEventId::SizeChanged => {
+                        let window_id = WindowId(window_id);
+
+                        if let Some(sdl_graphic) = sdl_graphics.get_mut(&window_id) {
+                            sdl_graphic.update_views();
+                            try!(render_back_channel.send_to((window_id.clone(), ToRender::GraphicsData(sdl_graphic.get_out_color(), sdl_graphic.get_out_depth()))));
+                        }
+
+                        try!(control_back_channel.send_to((window_id, ToControl::Resize(data1 as u32, data2 as u32))));
+                    },
+                    WindowEventId::FocusGained => {
+                        focus.focus_gained(WindowId(window_id));
+                        try!(control_back_channel.send_to((WindowId(window_id), ToControl::Focus(true))));
+                    },
+                    WindowEventId::FocusLost => {
+                        if focus.focus_lost(&WindowId(window_id)) {
+                            try!(control_back_channel.send_to((WindowId(window_id), ToControl::Focus(false))));
+                        }
                     },
                     _ => (),
                 }
@@ -37,17 +89,159 @@ pub fn handle_events(event_pump: &mut EventPump, render_back_channel: &mut Front
                 scancode,
                 keymod,
                 repeat,
+            } => {
+                if !focus.accepts(&WindowId(window_id)) {
+                    continue;
+                }
+
+                let action = keycode.and_then(|keycode| bindings.get_key_action(keycode, keymod));
+
+                if console_window.is_some() {
+                    let console_key = match keycode {
+                        Some(Keycode::Return) | Some(Keycode::KpEnter) => Some(ConsoleKey::Submit),
+                        Some(Keycode::Backspace) => Some(ConsoleKey::Erase),
+                        Some(Keycode::Tab) => Some(ConsoleKey::Complete),
+                        Some(Keycode::Up) => Some(ConsoleKey::HistoryPrev),
+                        Some(Keycode::Down) => Some(ConsoleKey::HistoryNext),
+                        _ => None,
+                    };
+
+                    if let Some(console_key) = console_key {
+                        try!(control_back_channel.send_to((WindowId(window_id), ToControl::ConsoleKey(console_key))));
+                    } else if !repeat && (keycode == Some(Keycode::Escape) || action == Some(Action::ToggleConsole)) {
+                        try!(toggle_console(window_manager, console_window, WindowId(window_id), control_back_channel));
+                    }
+                    continue;
+                }
+
+                if repeat {
+                    if let Some(action) = action {
+                        for target in focus.get_targets(&WindowId(window_id), action) {
+                            try!(control_back_channel.send_to((target, ToControl::ActionRepeat(action))));
+                        }
+                    }
+                    continue;
+                }
+
+                match action {
+                    Some(Action::Quit) => return Ok(true),
+                    Some(Action::ReloadBindings) => match bindings.reload() {
+                        Ok(()) => warn!("Reloaded bindings"),
+                        Err(err) => error!("Unable to reload bindings, keeping the old ones: {}", err),
+                    },
+                    Some(Action::ToggleConsole) => {
+                        try!(toggle_console(window_manager, console_window, WindowId(window_id), control_back_channel));
+                        console_opened = true;
+                    },
+                    Some(action) => for target in focus.get_targets(&WindowId(window_id), action) {
+                        try!(control_back_channel.send_to((target, ToControl::Action(action, true))));
+                    },
+                    None => (),
+                }
+            },
+            Event::KeyUp {
+                window_id,
+                keycode,
+                ..
             } => {
                 if let Some(keycode) = keycode {
-                    match keycode {
-                        Keycode::Escape => return true,
-                        _ => (),
+                    for action in bindings.get_key_actions(keycode) {
+                        for target in focus.get_targets(&WindowId(window_id), action) {
+                            try!(control_back_channel.send_to((target, ToControl::Action(action, false))));
+                        }
                     }
                 }
-            }
+            },
+            Event::TextInput {
+                window_id,
+                text,
+                ..
+            } => {
+                // The key that opened the console is echoed back as text in the same batch
+                if console_opened || console_window.as_ref() != Some(&WindowId(window_id)) {
+                    continue;
+                }
+
+                try!(control_back_channel.send_to((WindowId(window_id), ToControl::ConsoleText(text))));
+            },
+            Event::MouseMotion {
+                window_id,
+                x,
+                y,
+                ..
+            } => {
+                try!(control_back_channel.send_to((WindowId(window_id), ToControl::MouseMoved(x.max(0) as u32, y.max(0) as u32))));
+            },
+            Event::MouseButtonDown {
+                window_id,
+                mouse_btn,
+                ..
+            } => {
+                if !focus.accepts(&WindowId(window_id)) {
+                    continue;
+                }
+
+                try!(control_back_channel.send_to((WindowId(window_id), ToControl::MouseInput(true, convert_mouse_button(mouse_btn)))));
+
+                if let Some(action) = bindings.get_mouse_action(mouse_btn) {
+                    try!(control_back_channel.send_to((WindowId(window_id), ToControl::Action(action, true))));
+                }
+            },
+            Event::MouseButtonUp {
+                window_id,
+                mouse_btn,
+                ..
+            } => {
+                try!(control_back_channel.send_to((WindowId(window_id), ToControl::MouseInput(false, convert_mouse_button(mouse_btn)))));
+
+                if let Some(action) = bindings.get_mouse_action(mouse_btn) {
+                    try!(control_back_channel.send_to((WindowId(window_id), ToControl::Action(action, false))));
+                }
+            },
             _ => (),
         }
     }
 
-    false
+    Ok(false)
+}
+
+pub fn handle_controller_events<S>(source: &mut S, mapper: &mut ControllerMapper, bindings: &Bindings, window_id: &WindowId, control_back_channel: &mut FrontChannel<WindowedToControl, WindowedFromControl>) -> Result<bool, CoreError>
+where S: ControllerSource {
+    while let Some(event) = source.poll_event() {
+        for (action, pressed) in mapper.map_event(bindings, event) {
+            match action {
+                Action::Quit if pressed => return Ok(true),
+                Action::ReloadBindings | Action::ToggleConsole => (),
+                _ => try!(control_back_channel.send_to((window_id.clone(), ToControl::Action(action, pressed)))),
+            }
+        }
+    }
+
+    Ok(false)
+}
+
+fn toggle_console(window_manager: &WindowManager, console_window: &mut Option<WindowId>, window_id: WindowId, control_back_channel: &mut FrontChannel<WindowedToControl, WindowedFromControl>) -> Result<(), CoreError> {
+    let open = console_window.is_none();
+
+    if open {
+        window_manager.start_text_input();
+        *console_window = Some(window_id.clone());
+    } else {
+        window_manager.stop_text_input();
+        *console_window = None;
+    }
+
+    try!(control_back_channel.send_to((window_id, ToControl::ConsoleToggle(open))));
+    Ok(())
+}
+
+fn convert_mouse_button(mouse_btn: SdlMouseButton) -> MouseButton {
+    match mouse_btn {
+        SdlMouseButton::Left => MouseButton::Left,
+        SdlMouseButton::Right => MouseButton::Right,
+        SdlMouseButton::Middle => MouseButton::Middle,
+        SdlMouseButton::X1 => MouseButton::Other(4),
+        SdlMouseButton::X2 => MouseButton::Other(5),
+        SdlMouseButton::Unknown => MouseButton::Other(0),
+    }
 }
@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+
+use art;
+use components::{RenderId};
+use event::{EventBus, EventError, FrontChannel, two_way_channel};
+use systems::control::{WindowedToControl, WindowedFromControl, ToControl};
+use systems::null_render::{NullRenderSystem};
+use systems::render::{WindowedToRender, WindowedFromRender, ToRender};
+use systems::replay::{InputLog};
+use systems::rng::{Seed};
+use utils::{TickNum, WindowId};
+
+use game::{make_cameras};
+use game::specs::{World};
+use ::{Config, CoreError, Game, RenderIds, RenderKey};
+
+pub struct Headless {
+    game: Game,
+    render_event_core: FrontChannel<WindowedToRender, WindowedFromRender>,
+    control_event_core: FrontChannel<WindowedToControl, WindowedFromControl>,
+    window_ids: Vec<WindowId>,
+}
+
+impl Headless {
+    pub fn new(config: &Config, seed: Seed, input_log: InputLog) -> Headless {
+        warn!("Starting Headless Game");
+        let (render_event_core, render_event_game) = two_way_channel::<WindowedToRender, WindowedFromRender>();
+        let (control_event_core, control_event_game) = two_way_channel::<WindowedToControl, WindowedFromControl>();
+
+        let window_ids: Vec<WindowId> = (1..config.get_windows().len() as u32 + 1).map(|id| WindowId(id)).collect();
+
+        let cameras = window_ids.iter().zip(config.get_windows()).flat_map(|(window_id, window_config)|
+            make_cameras(window_id.clone(), window_config, config.get_projection())
+        ).collect();
+
+        let mut render_ids: RenderIds = HashMap::new();
+
+        for window_id in &window_ids {
+            render_ids.insert(RenderKey::new(window_id.clone(), art::tiles::ID), RenderId::new(window_id.clone(), 0));
+        }
+
+        let game = Game::new(
+            render_ids,
+            NullRenderSystem::new(render_event_game),
+            control_event_game,
+            input_log,
+            cameras,
+            config.get_ticks_per_second(),
+            seed
+        );
+
+        Headless {
+            game: game,
+            render_event_core: render_event_core,
+            control_event_core: control_event_core,
+            window_ids: window_ids,
+        }
+    }
+
+    pub fn run(&mut self, frames: u64) -> bool {
+        let tick_length = self.game.get_tick_length();
+
+        for _ in 0..frames {
+            if !self.game.advance(tick_length) {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    pub fn send_control(&mut self, window_id: WindowId, event: ToControl) -> Result<(), EventError> {
+        self.control_event_core.send_to((window_id, event))
+    }
+
+    pub fn get_event_bus(&self) -> &EventBus {
+        self.game.get_event_bus()
+    }
+
+    pub fn get_ticks(&self) -> TickNum {
+        self.game.get_ticks()
+    }
+
+    pub fn get_window_ids(&self) -> &[WindowId] {
+        self.window_ids.as_slice()
+    }
+
+    pub fn get_world(&mut self) -> &World {
+        self.game.get_world()
+    }
+
+    pub fn get_mut_world(&mut self) -> &mut World {
+        self.game.get_mut_world()
+    }
+
+    pub fn shutdown(mut self) -> Result<TickNum, EventError> {
+        for window_id in &self.window_ids {
+            try!(self.control_event_core.send_to((window_id.clone(), ToControl::Quit)));
+            try!(self.render_event_core.send_to((window_id.clone(), ToRender::Quit)));
+        }
+
+        let tick_length = self.game.get_tick_length();
+        self.game.advance(tick_length);
+
+        Ok(self.game.shutdown())
+    }
+}
+
+pub fn start_headless(config: Config, seed: Seed, frames: u64, input_log: InputLog) -> Result<TickNum, CoreError> {
+    let mut headless = Headless::new(&config, seed, input_log);
+
+    warn!("Running {} Headless Frames", frames);
+    headless.run(frames);
+
+    let ticks = try!(headless.shutdown());
+    warn!("Headless game exited after {} ticks", ticks);
+    Ok(ticks)
+}
+
+#[cfg(test)]
+mod tests {
+    use components::{Camera, RenderId};
+    use systems::control::{QuitFlag, ToControl};
+    use systems::input::{Action};
+    use systems::replay::{InputLog};
+    use utils::{WindowId};
+
+    use game::specs::{Join};
+    use ::{Config};
+    use super::{Headless};
+
+    fn camera_offsets(headless: &mut Headless, window_id: &WindowId) -> Vec<(f32, f32)> {
+        let cameras = headless.get_world().read::<Camera>();
+        (&cameras).iter().filter(|camera| camera.clone_window_id() == *window_id).map(|camera| {
+            let offset = camera.get_offset();
+            (offset.x, offset.y)
+        }).collect()
+    }
+
+    #[test]
+    fn runs_frames_without_a_window() {
+        let config = Config::default();
+        let mut headless = Headless::new(&config, 1, InputLog::Live);
+
+        assert!(headless.run(10));
+        assert_eq!(headless.get_ticks(), 10);
+        assert_eq!(headless.get_window_ids().len(), config.get_windows().len());
+
+        let camera_count = config.get_windows().iter().map(|window_config| window_config.get_cameras().len()).sum::<usize>();
+        assert_eq!((&headless.get_world().read::<Camera>()).iter().count(), camera_count);
+        assert_eq!((&headless.get_world().read::<RenderId>()).iter().count(), config.get_windows().len());
+
+        // Shutdown runs one last frame to deliver the quit
+        assert_eq!(headless.shutdown(), Ok(11));
+    }
+
+    #[test]
+    fn input_only_moves_its_window() {
+        let mut headless = Headless::new(&Config::default(), 1, InputLog::Live);
+        let first = headless.get_window_ids()[0].clone();
+        let second = headless.get_window_ids()[1].clone();
+
+        let before = camera_offsets(&mut headless, &second);
+
+        headless.send_control(first.clone(), ToControl::Action(Action::MoveRight, true)).unwrap();
+        assert!(headless.run(5));
+
+        assert!(camera_offsets(&mut headless, &first).iter().all(|&(x, y)| x > 0.0 && y == 0.0));
+        assert_eq!(camera_offsets(&mut headless, &second), before);
+    }
+
+    #[test]
+    fn quit_stops_the_run() {
+        let mut headless = Headless::new(&Config::default(), 1, InputLog::Live);
+        let window_id = headless.get_window_ids()[0].clone();
+
+        headless.send_control(window_id, ToControl::Quit).unwrap();
+
+        assert!(!headless.run(10));
+        assert_eq!(headless.get_ticks(), 1);
+        assert!(headless.get_world().read_resource::<QuitFlag>().is_set());
+    }
+}
@@ -10,25 +10,44 @@ extern crate math;
 extern crate systems;
 extern crate utils;
 
+use std::any::{Any};
 use std::thread;
 use std::collections::HashMap;
 
 use art::{RenderType};
-pub use dependencies::{find_folder, sdl2};
+pub use dependencies::{find_folder, glutin, rand, sdl2, toml};
 use components::{RenderId};
-use event::{two_way_channel};
-use graphics::{Device, OutColor, OutDepth};
-use graphics::rl_sdl2::{build_graphics_sdl};
-use math::{OrthographicHelper};
-use systems::control::{WindowedFromControl, WindowedToControl, FromControl};
-use systems::render::{RenderSystemSend, WindowedFromRender, WindowedToRender, FromRender, ToRender};
+use event::{FrontChannel, two_way_channel};
+use graphics::{Device, OutColor, OutDepth, make_materials, make_batch_shaders};
+use graphics::rl_sdl2::{SdlGraphic, build_graphics_sdl};
+use sdl2::{EventPump};
+use systems::control::{WindowedFromControl, WindowedToControl, FromControl, ToControl};
+use systems::replay::{InputLog, InputRecorder, InputReplay};
+use systems::rng::{Seed};
+use systems::render::{RenderSystem, RenderSystemSend, WindowedFromRender, WindowedToRender, FromRender, ToRender};
 use utils::{WindowId};
 
-mod game;
+pub mod bindings;
+pub mod config;
+pub mod controller;
+pub mod error;
+pub mod focus;
+pub mod game;
+pub mod headless;
+pub mod options;
+pub mod windows;
 mod handle_events;
 
-use game::Game;
-use handle_events::{handle_events};
+pub use bindings::{Binding, Bindings, BindingsError};
+pub use config::{CameraConfig, Config, ConfigError, WindowConfig};
+pub use controller::{ControllerEvent, ControllerMapper, ControllerSource, SdlControllerSource, SyntheticControllerSource};
+pub use error::{CoreError};
+pub use focus::{FocusState};
+pub use game::{Game, make_camera, make_cameras};
+pub use headless::{Headless, start_headless};
+pub use options::{Options};
+pub use windows::{WindowManager};
+use handle_events::{handle_events, handle_controller_events};
 
 #[derive(Hash, Eq, PartialEq)]
 pub struct RenderKey {
@@ -55,35 +74,73 @@ impl RenderKey {
 
 pub type RenderIds = HashMap<RenderKey, RenderId>;
 
-pub fn start() {
-    warn!("Starting Core Start");
-    let (width, height): (u32, u32) = (640, 480);
-
-    let left = -10.0;
-    let right = 10.0;
-
-    let near = 0.0;
-    let far = 10.0;
-
-    let aspect_ratio = width as f32 / height as f32;
+pub fn run(mut config: Config, options: Options) -> Result<(), CoreError> {
+    if let Some(save_file) = options.get_save_file() {
+        return Err(CoreError::Unsupported(format!("unable to load save file {:?}: save files are not supported yet", save_file)));
+    }
 
-    let ortho_helper = OrthographicHelper::new(aspect_ratio, left, right, near, far);
+    let replay = match options.get_replay_file() {
+        Some(replay_file) => {
+            warn!("Loading Replay: {:?}", replay_file);
+            Some(try!(InputReplay::load(replay_file)))
+        },
+        None => None,
+    };
+
+    let seed = match replay {
+        Some(ref replay) => {
+            if options.get_seed().map_or(false, |seed| seed != replay.get_seed()) {
+                warn!("Ignoring seed option, the replay was recorded with seed {}", replay.get_seed());
+            }
+            config.set_ticks_per_second(replay.get_ticks_per_second());
+            replay.get_seed()
+        },
+        None => options.get_seed().unwrap_or_else(|| rand::random()),
+    };
+    warn!("Using Seed: {}", seed);
+
+    let input_log = match (replay, options.get_record_file()) {
+        (Some(replay), _) => InputLog::Replay(replay),
+        (None, Some(record_file)) => {
+            warn!("Recording Input To: {:?}", record_file);
+            InputLog::Record(try!(InputRecorder::create(record_file, seed, config.get_ticks_per_second())))
+        },
+        (None, None) => InputLog::Live,
+    };
+
+    match options.get_headless_frames() {
+        Some(frames) => {
+            try!(start_headless(config, seed, frames, input_log));
+            Ok(())
+        },
+        None => start(config, seed, input_log),
+    }
+}
 
+pub fn start(config: Config, seed: Seed, input_log: InputLog) -> Result<(), CoreError> {
+    warn!("Starting Core Start");
     warn!("Building SDL Graphics");
-    let (sdl, mut sdl_graphic_encoders) = build_graphics_sdl(vec!(("First Window", 640, 480), ("Second Window", 640, 480)));
+    let (sdl, mut sdl_graphic_encoders) = try!(build_graphics_sdl(
+        config.get_windows().iter().map(|window| (window.get_title(), window.get_width(), window.get_height())).collect()
+    ));
 
     warn!("Make two way channels");
     let (mut render_event_core, render_event_game) = two_way_channel::<WindowedToRender, WindowedFromRender>();
     let (mut control_event_core, control_event_game) = two_way_channel::<WindowedToControl, WindowedFromControl>();
 
     let mut outs: HashMap<WindowId, (OutColor, OutDepth)> = HashMap::new();
+    let mut clear_colors: HashMap<WindowId, [f32; 4]> = HashMap::new();
     let mut stage_two_encoder = HashMap::new();
 
     let mut sdl_graphics = HashMap::new();
+    let mut cameras = vec!();
+    let mut focus = FocusState::new();
 
     warn!("Sdl Graphic Encoders Len: {:?}", sdl_graphic_encoders.len());
 
-    for (window_id, sdl_graphic_encoder) in sdl_graphic_encoders.drain() {
+    let primary_id = try!(sdl_graphic_encoders.first().map(|&(ref window_id, _)| window_id.clone()).ok_or_else(|| CoreError::Sdl("no windows were created".to_string())));
+
+    for ((window_id, sdl_graphic_encoder), window_config) in sdl_graphic_encoders.drain(..).zip(config.get_windows()) {
         // let ((mut out_color, mut out_depth), mut factory, mut encoder, mut window, mut device, context) = sdl_graphic;
 
         let (encoder, sdl_graphic) = sdl_graphic_encoder.take_encoder();
@@ -92,24 +149,36 @@ pub fn start() {
         let out_depth = sdl_graphic.get_out_depth();
 
         outs.insert(window_id.clone(), (out_color, out_depth));
+        clear_colors.insert(window_id.clone(), window_config.get_clear_color());
+        focus.set_global_shortcuts(window_id.clone(), window_config.get_global_shortcuts());
+        cameras.extend(make_cameras(window_id.clone(), window_config, config.get_projection()));
+
+        let (width, height) = sdl_graphic.get_window().size();
+        try!(control_event_core.send_to((window_id.clone(), ToControl::Resize(width, height))));
         sdl_graphics.insert(window_id.clone(), sdl_graphic);
 
-        render_event_core.send_to((window_id.clone(), ToRender::Encoder(encoder.clone_empty())));
+        try!(render_event_core.send_to((window_id.clone(), ToRender::Encoder(encoder.clone_empty()))));
         stage_two_encoder.insert(window_id, encoder);
     }
 
     for (window_id, encoder) in stage_two_encoder.drain() {
-        render_event_core.send_to((window_id, ToRender::Encoder(encoder)));
+        try!(render_event_core.send_to((window_id, ToRender::Encoder(encoder))));
     }
 
+    warn!("Finding shaders folder");
+    let shaders_folder = try!(config.find_shaders_dir());
+
+    let materials = try!(make_materials(&shaders_folder));
+    let batch_shaders = try!(make_batch_shaders(&shaders_folder));
+
     warn!("Making Render System");
-    let mut render_system = RenderSystemSend::new(outs);
+    let mut render_system = RenderSystemSend::new(outs, clear_colors, materials.clone(), batch_shaders.clone());
 
     warn!("Making Square Render");
     let packet = art::make_square_render();
 
     warn!("Finding assets folder");
-    let assets_folder = find_folder::Search::ParentsThenKids(3, 3).for_folder("assets").unwrap_or_else(|err| panic!("Unable to find Assets Folder: {:?}", err));
+    let assets_folder = try!(config.find_assets_dir());
 
     let mut render_ids: RenderIds = HashMap::new();
 
@@ -118,18 +187,21 @@ pub fn start() {
     warn!("Making Tiles Render");
     for (window_id, mut sdl_graphic) in sdl_graphics.iter_mut() {
         let tiles_render = {
-            let texture = graphics::textures::load_texture(
+            let texture = try!(graphics::textures::load_texture(
                 sdl_graphic.get_mut_factory(),
                 assets_folder.join(
                     art::tiles::NAME
                 )
-            );
-            render_system.add_render(
+            ));
+            let (factory, cache) = sdl_graphic.get_mut_factory_and_cache();
+            try!(render_system.add_render(
                 window_id.clone(),
-                sdl_graphic.get_mut_factory(),
+                factory,
+                cache,
                 &packet,
-                texture
-            )
+                texture,
+                &art::tiles::make_texture_settings()
+            ))
         };
 
         render_ids.insert(RenderKey::new(window_id.clone(), art::tiles::ID), tiles_render);
@@ -137,51 +209,125 @@ pub fn start() {
 
     warn!("Render Ids Len: {:?}", render_ids.len());
 
+    let window_manager = WindowManager::new(
+        try!(sdl.video().map_err(CoreError::Sdl)),
+        materials,
+        batch_shaders,
+        art::make_square_render(),
+        assets_folder,
+        config.get_projection().clone(),
+        primary_id
+    );
+    window_manager.stop_text_input();
+
+    warn!("Loading Bindings");
+    let mut bindings = try!(Bindings::load_or_default(config.get_bindings_file()));
+
+    warn!("Opening Game Controller Subsystem");
+    let mut controllers = SdlControllerSource::new(try!(sdl.game_controller().map_err(CoreError::Sdl)));
+    let mut controller_mapper = ControllerMapper::new(config.get_controller_deadzone(), config.get_controller_trigger_threshold());
+
+    warn!("Creating Event Pump");
+    let mut event_pump = try!(sdl.event_pump().map_err(CoreError::Sdl));
+    event_pump.enable_event(sdl2::event::EventType::Window);
+    event_pump.enable_event(sdl2::event::EventType::Quit);
+
     warn!("Making Game");
     let game = Game::new(
         render_ids,
-        render_system,
-        render_event_game,
+        RenderSystem::new(render_event_game, render_system),
         control_event_game,
-        ortho_helper
+        input_log,
+        cameras,
+        config.get_ticks_per_second(),
+        seed
     );
 
     warn!("Starting Game Thread");
     let game_handle = thread::spawn(|| {
         let mut game = game;
         while game.frame() { }
+        game.shutdown()
     });
 
-    warn!("Creating Event Pump");
-    let mut event_pump = sdl.event_pump().unwrap_or_else(|err| panic!("Error while making event pump: {:?}", err));
-    event_pump.enable_event(sdl2::event::EventType::Window);
-    event_pump.enable_event(sdl2::event::EventType::Quit);
-
     warn!("Entering Main Loop");
-    'main: loop {
+    let main_loop_result = match main_loop(&mut event_pump, &window_manager, &mut bindings, &mut controllers, &mut controller_mapper, &mut focus, &mut sdl_graphics, &mut render_event_core, &mut control_event_core) {
+        // The game thread drops its channels when it stops by itself, a panic is still reported by the join below
+        Err(CoreError::Event(err)) => {
+            warn!("Game stopped: {}", err);
+            Ok(())
+        },
+        result => result,
+    };
+
+    if let Err(ref err) = main_loop_result {
+        error!("Main loop stopped: {}", err);
+    }
+
+    warn!("Sending Quit to Game");
+    for window_id in sdl_graphics.keys() {
+        // A failed send means the game thread is already gone, the join below reports why
+        control_event_core.send_to((window_id.clone(), ToControl::Quit)).unwrap_or(());
+        render_event_core.send_to((window_id.clone(), ToRender::Quit)).unwrap_or(());
+    }
+
+    warn!("Joining Game Thread");
+    let ticks = try!(game_handle.join().map_err(|err| CoreError::GameThread(panic_message(err))));
+    warn!("Game thread exited after {} ticks", ticks);
+
+    main_loop_result
+}
+
+fn main_loop(
+    event_pump: &mut EventPump,
+    window_manager: &WindowManager,
+    bindings: &mut Bindings,
+    controllers: &mut SdlControllerSource,
+    controller_mapper: &mut ControllerMapper,
+    focus: &mut FocusState,
+    sdl_graphics: &mut HashMap<WindowId, SdlGraphic>,
+    render_event_core: &mut FrontChannel<WindowedToRender, WindowedFromRender>,
+    control_event_core: &mut FrontChannel<WindowedToControl, WindowedFromControl>
+) -> Result<(), CoreError> {
+    let mut console_window = None;
+    let mut titles: HashMap<WindowId, String> = HashMap::new();
+
+    loop {
         // warn!("Looping Main Loop");
-        if let Some(event) = render_event_core.try_recv_from() {
+        if let Some(event) = try!(render_event_core.try_recv_from()) {
             match event {//render_event_core.recv_from() {
                 (window_id, FromRender::Encoder(mut encoder)) => {
                     // warn!("Handling Events");
-                    if handle_events(&mut event_pump, &mut render_event_core, &mut control_event_core) {
-                        break 'main;
+                    if try!(handle_events(event_pump, window_manager, bindings, controllers, focus, &mut console_window, sdl_graphics, render_event_core, control_event_core)) {
+                        return Ok(());
+                    }
+
+                    // Controllers aren't tied to a window, they drive whichever one has focus
+                    let controller_target = focus.get_focused().cloned().unwrap_or_else(|| window_manager.get_primary_id().clone());
+                    if try!(handle_controller_events(controllers, controller_mapper, bindings, &controller_target, control_event_core)) {
+                        return Ok(());
                     }
 
-                    let sdl_graphic = sdl_graphics.get_mut(&window_id).unwrap_or_else(|| panic!("Unable to find SdlGraphic for: {:?}", window_id));
+                    let sdl_graphic = match sdl_graphics.get_mut(&window_id) {
+                        Some(sdl_graphic) => sdl_graphic,
+                        None => {
+                            warn!("Dropping encoder for closed window: {:?}", window_id);
+                            continue;
+                        },
+                    };
 
                     // warn!("Making Context Current");
                     warn!("Before: {:?}", sdl_graphic.get_gl_context().is_current());
                     if !sdl_graphic.get_gl_context().is_current() {
-                        sdl_graphic.get_window().subsystem().gl_release_current_context().unwrap_or_else(|err| panic!("Release Context Error: {:?}", err));
+                        try!(sdl_graphic.get_window().subsystem().gl_release_current_context().map_err(CoreError::Sdl));
                         // sdl_graphic.get_window().gl_set_context_to_current().unwrap_or_else(|err| panic!("Set Context To Current Error: {:?}", err));
-                        sdl_graphic.get_window().gl_make_current(sdl_graphic.get_gl_context()).unwrap_or_else(|err| panic!("Make Current Error: {:?}", err));
+                        try!(sdl_graphic.get_window().gl_make_current(sdl_graphic.get_gl_context()).map_err(CoreError::Sdl));
                     }
                     warn!("After: {:?}", sdl_graphic.get_gl_context().is_current());
                     // warn!("Flushing Encoder");
                     encoder.flush(sdl_graphic.get_mut_device());
                     // warn!("Sending Encoder Back");
-                    render_event_core.send_to((window_id, ToRender::Encoder(encoder)));
+                    try!(render_event_core.send_to((window_id, ToRender::Encoder(encoder))));
                     // warn!("Swapping Windows");
                     sdl_graphic.get_mut_window().gl_swap_window();
                     // warn!("Cleanup");
@@ -190,14 +336,61 @@ pub fn start() {
             }
         }
 
-        if let Some(event) = control_event_core.try_recv_from() {
+        if let Some(event) = try!(control_event_core.try_recv_from()) {
             match event {
-                (_, FromControl::Resize) => { //_ = window_id
+                (parent_id, FromControl::OpenWindow(title, width, height, clear_color)) => {
+                    warn!("Opening window requested by: {:?}", parent_id);
+                    let window_config = WindowConfig::new(title, width, height, clear_color);
+                    let window_id = try!(window_manager.open(&window_config, sdl_graphics, render_event_core, control_event_core));
+                    focus.set_global_shortcuts(window_id, window_config.get_global_shortcuts());
+                },
+                (window_id, FromControl::CloseWindow) => {
+                    if console_window.as_ref() == Some(&window_id) {
+                        window_manager.stop_text_input();
+                        console_window = None;
+                    }
 
+                    focus.remove_window(&window_id);
+
+                    if try!(window_manager.close(window_id, sdl_graphics, render_event_core, control_event_core)) {
+                        return Ok(());
+                    }
+                },
+                (window_id, FromControl::ConsoleOverlay(overlay)) => {
+                    if let Some(sdl_graphic) = sdl_graphics.get_mut(&window_id) {
+                        let title = match overlay {
+                            Some(overlay) => format!("{} | {}", titles.entry(window_id.clone()).or_insert_with(|| sdl_graphic.get_window().title().to_string()), overlay),
+                            None => match titles.remove(&window_id) {
+                                Some(title) => title,
+                                None => continue,
+                            },
+                        };
+
+                        try!(sdl_graphic.get_mut_window().set_title(&title).map_err(|err| CoreError::Sdl(format!("{:?}", err))));
+                    }
+                },
+                (window_id, FromControl::ReloadAssets) => {
+                    warn!("Reloading assets requested by: {:?}", window_id);
+                    try!(window_manager.reload_assets(sdl_graphics, render_event_core));
+
+                    match bindings.reload() {
+                        Ok(()) => warn!("Reloaded bindings"),
+                        Err(err) => error!("Unable to reload bindings, keeping the old ones: {}", err),
+                    }
                 },
             }
         }
     }
+}
+
+fn panic_message(err: Box<Any + Send>) -> String {
+    if let Some(message) = err.downcast_ref::<&'static str>() {
+        return message.to_string();
+    }
+
+    if let Some(message) = err.downcast_ref::<String>() {
+        return message.clone();
+    }
 
-    // game_handle.join().unwrap_or_else(|err| panic!("Error while joining game handle: {:?}", err));
+    "unknown panic".to_string()
 }
@@ -0,0 +1,64 @@
+use std::path::{Path, PathBuf};
+
+use systems::rng::{Seed};
+
+#[derive(Debug, Clone)]
+pub struct Options {
+    seed: Option<Seed>,
+    headless_frames: Option<u64>,
+    save_file: Option<PathBuf>,
+    record_file: Option<PathBuf>,
+    replay_file: Option<PathBuf>,
+}
+
+impl Options {
+    pub fn new() -> Options {
+        Options {
+            seed: None,
+            headless_frames: None,
+            save_file: None,
+            record_file: None,
+            replay_file: None,
+        }
+    }
+
+    pub fn set_seed(&mut self, seed: Seed) {
+        self.seed = Some(seed);
+    }
+
+    pub fn set_headless_frames(&mut self, frames: u64) {
+        self.headless_frames = Some(frames);
+    }
+
+    pub fn set_save_file(&mut self, path: PathBuf) {
+        self.save_file = Some(path);
+    }
+
+    pub fn set_record_file(&mut self, path: PathBuf) {
+        self.record_file = Some(path);
+    }
+
+    pub fn set_replay_file(&mut self, path: PathBuf) {
+        self.replay_file = Some(path);
+    }
+
+    pub fn get_seed(&self) -> Option<Seed> {
+        self.seed
+    }
+
+    pub fn get_headless_frames(&self) -> Option<u64> {
+        self.headless_frames
+    }
+
+    pub fn get_save_file(&self) -> Option<&Path> {
+        self.save_file.as_ref().map(|path| path.as_path())
+    }
+
+    pub fn get_record_file(&self) -> Option<&Path> {
+        self.record_file.as_ref().map(|path| path.as_path())
+    }
+
+    pub fn get_replay_file(&self) -> Option<&Path> {
+        self.replay_file.as_ref().map(|path| path.as_path())
+    }
+}
@@ -0,0 +1,172 @@
+use std::collections::{HashMap};
+use std::path::{PathBuf};
+
+use art::{layers, tiles};
+use components::{RenderData, RenderId};
+use event::{FrontChannel};
+use graphics::{Bundle, Materials, Packet, Shaders, SpriteBatch, DEFAULT_BATCH_CAPACITY, load_texture};
+use graphics::rl_sdl2::{SdlGraphic, build_window_sdl};
+use sdl2::video::{VideoSubsystem};
+use systems::control::{WindowedToControl, WindowedFromControl, ToControl};
+use systems::render::{WindowedToRender, WindowedFromRender, ToRender, make_bundle, make_sprite_batch};
+use utils::{WindowId};
+
+use config::{ProjectionConfig, WindowConfig};
+use ::{CoreError, make_cameras};
+
+pub struct WindowManager {
+    video: VideoSubsystem,
+    materials: Materials,
+    batch_shaders: Shaders,
+    packet: Packet,
+    assets_folder: PathBuf,
+    projection: ProjectionConfig,
+    primary_id: WindowId,
+}
+
+impl WindowManager {
+    pub fn new(video: VideoSubsystem, materials: Materials, batch_shaders: Shaders, packet: Packet, assets_folder: PathBuf, projection: ProjectionConfig, primary_id: WindowId) -> WindowManager {
+        WindowManager {
+            video: video,
+            materials: materials,
+            batch_shaders: batch_shaders,
+            packet: packet,
+            assets_folder: assets_folder,
+            projection: projection,
+            primary_id: primary_id,
+        }
+    }
+
+    pub fn get_primary_id(&self) -> &WindowId {
+        &self.primary_id
+    }
+
+    pub fn open(
+        &self,
+        window_config: &WindowConfig,
+        sdl_graphics: &mut HashMap<WindowId, SdlGraphic>,
+        render_event_core: &mut FrontChannel<WindowedToRender, WindowedFromRender>,
+        control_event_core: &mut FrontChannel<WindowedToControl, WindowedFromControl>
+    ) -> Result<WindowId, CoreError> {
+        let (window_id, sdl_graphic_encoder) = try!(build_window_sdl(
+            &self.video,
+            (window_config.get_title(), window_config.get_width(), window_config.get_height())
+        ));
+        warn!("Opening Window: {:?}", window_id);
+
+        let (encoder, mut sdl_graphic) = sdl_graphic_encoder.take_encoder();
+
+        let out_color = sdl_graphic.get_out_color();
+        let out_depth = sdl_graphic.get_out_depth();
+
+        let (tiles_bundle, tiles_batch) = try!(self.make_tiles_bundle(&mut sdl_graphic));
+
+        let renders = vec!(
+            (RenderId::new(window_id.clone(), 0), RenderData::new(layers::TILES, tiles::DEFAULT_TINT.clone(), tiles::EMPTY, tiles::SIZE))
+        );
+
+        try!(render_event_core.send_to((window_id.clone(), ToRender::OpenWindow(out_color, out_depth, window_config.get_clear_color(), vec!(tiles_bundle), vec!(tiles_batch)))));
+        let cameras = make_cameras(window_id.clone(), window_config, &self.projection);
+
+        try!(control_event_core.send_to((window_id.clone(), ToControl::WindowOpened(renders, cameras))));
+
+        let (width, height) = sdl_graphic.get_window().size();
+        try!(control_event_core.send_to((window_id.clone(), ToControl::Resize(width, height))));
+
+        try!(render_event_core.send_to((window_id.clone(), ToRender::Encoder(encoder.clone_empty()))));
+        try!(render_event_core.send_to((window_id.clone(), ToRender::Encoder(encoder))));
+
+        sdl_graphics.insert(window_id.clone(), sdl_graphic);
+
+        Ok(window_id)
+    }
+
+    pub fn reload_assets(
+        &self,
+        sdl_graphics: &mut HashMap<WindowId, SdlGraphic>,
+        render_event_core: &mut FrontChannel<WindowedToRender, WindowedFromRender>
+    ) -> Result<(), CoreError> {
+        for (window_id, sdl_graphic) in sdl_graphics.iter_mut() {
+            warn!("Reloading Assets for: {:?}", window_id);
+            let (tiles_bundle, tiles_batch) = try!(self.make_tiles_bundle(sdl_graphic));
+            try!(render_event_core.send_to((window_id.clone(), ToRender::ReplaceBundles(vec!(tiles_bundle), vec!(tiles_batch)))));
+        }
+
+        Ok(())
+    }
+
+    pub fn start_text_input(&self) {
+        self.video.text_input().start();
+    }
+
+    pub fn stop_text_input(&self) {
+        self.video.text_input().stop();
+    }
+
+    pub fn close(
+        &self,
+        window_id: WindowId,
+        sdl_graphics: &mut HashMap<WindowId, SdlGraphic>,
+        render_event_core: &mut FrontChannel<WindowedToRender, WindowedFromRender>,
+        control_event_core: &mut FrontChannel<WindowedToControl, WindowedFromControl>
+    ) -> Result<bool, CoreError> {
+        if !sdl_graphics.contains_key(&window_id) {
+            warn!("Ignoring close for unknown window: {:?}", window_id);
+            return Ok(false);
+        }
+
+        if window_id == self.primary_id || sdl_graphics.len() == 1 {
+            warn!("Closing last or primary window: {:?}", window_id);
+            return Ok(true);
+        }
+
+        warn!("Closing Window: {:?}", window_id);
+        sdl_graphics.remove(&window_id);
+
+        try!(render_event_core.send_to((window_id.clone(), ToRender::CloseWindow)));
+        try!(control_event_core.send_to((window_id, ToControl::WindowClosed)));
+
+        Ok(false)
+    }
+
+    fn make_tiles_bundle(&self, sdl_graphic: &mut SdlGraphic) -> Result<(Bundle, SpriteBatch), CoreError> {
+        let out_color = sdl_graphic.get_out_color();
+        let out_depth = sdl_graphic.get_out_depth();
+
+        let texture = try!(load_texture(
+            sdl_graphic.get_mut_factory(),
+            self.assets_folder.join(
+                tiles::NAME
+            )
+        ));
+
+        let texture_settings = tiles::make_texture_settings();
+
+        let (factory, cache) = sdl_graphic.get_mut_factory_and_cache();
+
+        let bundle = try!(make_bundle(
+            factory,
+            cache,
+            &self.materials,
+            &self.packet,
+            texture.clone(),
+            &texture_settings,
+            out_color.clone(),
+            out_depth.clone()
+        ));
+
+        let batch = try!(make_sprite_batch(
+            factory,
+            cache,
+            &self.batch_shaders,
+            &self.packet,
+            texture,
+            &texture_settings,
+            out_color,
+            out_depth,
+            DEFAULT_BATCH_CAPACITY
+        ));
+
+        Ok((bundle, batch))
+    }
+}
@@ -5,9 +5,12 @@ pub extern crate gfx;
 pub extern crate gfx_device_gl;
 pub extern crate gfx_window_glutin;
 pub extern crate gfx_window_sdl;
+pub extern crate getopts;
 pub extern crate glutin;
 pub extern crate image;
 pub extern crate log;
+pub extern crate rand;
 pub extern crate sdl2;
 pub extern crate specs;
 pub extern crate time;
+pub extern crate toml;
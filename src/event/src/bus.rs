@@ -0,0 +1,402 @@
+use std::any::{Any, TypeId};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::marker::{PhantomData};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use utils::{WindowId};
+
+pub type PublisherId = u32;
+
+pub type SubscriberId = u32;
+
+#[derive(Debug, Clone)]
+pub struct Envelope<T> {
+    publisher: PublisherId,
+    scope: Option<WindowId>,
+    event: T,
+}
+
+impl<T> Envelope<T> {
+    pub fn get_publisher(&self) -> PublisherId {
+        self.publisher
+    }
+
+    pub fn get_scope(&self) -> Option<&WindowId> {
+        self.scope.as_ref()
+    }
+
+    pub fn get_event(&self) -> &T {
+        &self.event
+    }
+
+    pub fn into_event(self) -> T {
+        self.event
+    }
+}
+
+struct Queue<T> {
+    scope: Option<WindowId>,
+    envelopes: VecDeque<Envelope<T>>,
+}
+
+struct Topic<T> {
+    pending: Vec<Envelope<T>>,
+    queues: HashMap<SubscriberId, Queue<T>>,
+}
+
+impl<T> Topic<T> {
+    fn new() -> Topic<T> {
+        Topic {
+            pending: vec!(),
+            queues: HashMap::new(),
+        }
+    }
+}
+
+trait AnyTopic: Send {
+    fn flush(&mut self);
+
+    fn unsubscribe(&mut self, subscriber: SubscriberId);
+
+    fn as_any_mut(&mut self) -> &mut Any;
+}
+
+impl<T> AnyTopic for Topic<T>
+where T: Clone + Send + 'static {
+    fn flush(&mut self) {
+        if self.pending.is_empty() {
+            return;
+        }
+
+        // Stable sort, each publisher keeps the order it published in
+        self.pending.sort_by_key(|envelope| envelope.publisher);
+
+        for envelope in self.pending.drain(..) {
+            for queue in self.queues.values_mut() {
+                let in_scope = match (queue.scope.as_ref(), envelope.scope.as_ref()) {
+                    (Some(scope), Some(event_scope)) => scope == event_scope,
+                    _ => true,
+                };
+
+                if in_scope {
+                    queue.envelopes.push_back(envelope.clone());
+                }
+            }
+        }
+    }
+
+    fn unsubscribe(&mut self, subscriber: SubscriberId) {
+        self.queues.remove(&subscriber);
+    }
+
+    fn as_any_mut(&mut self) -> &mut Any {
+        self
+    }
+}
+
+struct BusInner {
+    topics: HashMap<TypeId, Box<AnyTopic>>,
+    next_publisher: PublisherId,
+    next_subscriber: SubscriberId,
+}
+
+impl BusInner {
+    fn get_mut_topic<T>(&mut self) -> &mut Topic<T>
+    where T: Clone + Send + 'static {
+        self.topics.entry(TypeId::of::<T>()).or_insert_with(|| Box::new(Topic::<T>::new()) as Box<AnyTopic>)
+            .as_any_mut()
+            .downcast_mut::<Topic<T>>()
+            .expect("event bus topic stored under the wrong type")
+    }
+}
+
+#[derive(Clone)]
+pub struct EventBus {
+    inner: Arc<Mutex<BusInner>>,
+}
+
+impl EventBus {
+    pub fn new() -> EventBus {
+        EventBus {
+            inner: Arc::new(Mutex::new(BusInner {
+                topics: HashMap::new(),
+                next_publisher: 0,
+                next_subscriber: 0,
+            })),
+        }
+    }
+
+    pub fn publisher(&self) -> Publisher {
+        let id = {
+            let mut inner = lock(&self.inner);
+            let id = inner.next_publisher;
+            inner.next_publisher += 1;
+            id
+        };
+
+        Publisher {
+            id: id,
+            bus: self.clone(),
+        }
+    }
+
+    pub fn subscribe<T>(&self) -> Subscriber<T>
+    where T: Clone + Send + 'static {
+        self.subscribe_scoped(None)
+    }
+
+    pub fn subscribe_window<T>(&self, window_id: WindowId) -> Subscriber<T>
+    where T: Clone + Send + 'static {
+        self.subscribe_scoped(Some(window_id))
+    }
+
+    fn subscribe_scoped<T>(&self, scope: Option<WindowId>) -> Subscriber<T>
+    where T: Clone + Send + 'static {
+        let mut inner = lock(&self.inner);
+
+        let id = inner.next_subscriber;
+        inner.next_subscriber += 1;
+
+        inner.get_mut_topic::<T>().queues.insert(id, Queue {
+            scope: scope,
+            envelopes: VecDeque::new(),
+        });
+
+        Subscriber {
+            id: id,
+            bus: self.clone(),
+            phantom: PhantomData,
+        }
+    }
+
+    pub fn flush(&self) {
+        let mut inner = lock(&self.inner);
+
+        for topic in inner.topics.values_mut() {
+            topic.flush();
+        }
+    }
+}
+
+impl fmt::Debug for EventBus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "EventBus")
+    }
+}
+
+#[derive(Debug)]
+pub struct Publisher {
+    id: PublisherId,
+    bus: EventBus,
+}
+
+impl Publisher {
+    pub fn get_id(&self) -> PublisherId {
+        self.id
+    }
+
+    pub fn publish<T>(&self, event: T)
+    where T: Clone + Send + 'static {
+        self.publish_scoped(None, event);
+    }
+
+    pub fn publish_window<T>(&self, window_id: WindowId, event: T)
+    where T: Clone + Send + 'static {
+        self.publish_scoped(Some(window_id), event);
+    }
+
+    fn publish_scoped<T>(&self, scope: Option<WindowId>, event: T)
+    where T: Clone + Send + 'static {
+        let mut inner = lock(&self.bus.inner);
+
+        inner.get_mut_topic::<T>().pending.push(Envelope {
+            publisher: self.id,
+            scope: scope,
+            event: event,
+        });
+    }
+}
+
+pub struct Subscriber<T>
+where T: Clone + Send + 'static {
+    id: SubscriberId,
+    bus: EventBus,
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Subscriber<T>
+where T: Clone + Send + 'static {
+    pub fn get_id(&self) -> SubscriberId {
+        self.id
+    }
+
+    pub fn drain(&mut self) -> Vec<Envelope<T>> {
+        let mut inner = lock(&self.bus.inner);
+
+        match inner.get_mut_topic::<T>().queues.get_mut(&self.id) {
+            Some(queue) => queue.envelopes.drain(..).collect(),
+            None => vec!(),
+        }
+    }
+
+    pub fn drain_events(&mut self) -> Vec<T> {
+        self.drain().into_iter().map(|envelope| envelope.into_event()).collect()
+    }
+}
+
+impl<T> fmt::Debug for Subscriber<T>
+where T: Clone + Send + 'static {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Subscriber({})", self.id)
+    }
+}
+
+impl<T> Drop for Subscriber<T>
+where T: Clone + Send + 'static {
+    fn drop(&mut self) {
+        let mut inner = lock(&self.bus.inner);
+
+        if let Some(topic) = inner.topics.get_mut(&TypeId::of::<T>()) {
+            topic.unsubscribe(self.id);
+        }
+    }
+}
+
+fn lock(inner: &Arc<Mutex<BusInner>>) -> MutexGuard<BusInner> {
+    // A panic while holding the lock can only leave queues partially flushed, so keep going
+    inner.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use utils::{WindowId};
+
+    use super::{EventBus};
+
+    #[test]
+    fn nothing_is_delivered_before_flush() {
+        let bus = EventBus::new();
+        let mut subscriber = bus.subscribe::<u32>();
+
+        bus.publisher().publish(1u32);
+        assert!(subscriber.drain_events().is_empty());
+
+        bus.flush();
+        assert_eq!(subscriber.drain_events(), vec!(1));
+        assert!(subscriber.drain_events().is_empty());
+    }
+
+    #[test]
+    fn flush_orders_by_publisher_then_publish_order() {
+        let bus = EventBus::new();
+        let first = bus.publisher();
+        let second = bus.publisher();
+        let mut subscriber = bus.subscribe::<&'static str>();
+
+        second.publish("second a");
+        first.publish("first a");
+        second.publish("second b");
+        first.publish("first b");
+        bus.flush();
+
+        assert_eq!(subscriber.drain_events(), vec!("first a", "first b", "second a", "second b"));
+    }
+
+    #[test]
+    fn every_subscriber_sees_the_same_order() {
+        let bus = EventBus::new();
+        let first = bus.publisher();
+        let second = bus.publisher();
+        let mut subscriber_a = bus.subscribe::<u32>();
+        let mut subscriber_b = bus.subscribe::<u32>();
+
+        second.publish(3u32);
+        first.publish(1u32);
+        second.publish(4u32);
+        first.publish(2u32);
+        bus.flush();
+
+        let envelopes = subscriber_a.drain();
+        assert_eq!(envelopes.iter().map(|envelope| envelope.get_publisher()).collect::<Vec<_>>(), vec!(first.get_id(), first.get_id(), second.get_id(), second.get_id()));
+        assert_eq!(envelopes.into_iter().map(|envelope| envelope.into_event()).collect::<Vec<_>>(), vec!(1, 2, 3, 4));
+        assert_eq!(subscriber_b.drain_events(), vec!(1, 2, 3, 4));
+    }
+
+    #[test]
+    fn flushes_keep_their_order() {
+        let bus = EventBus::new();
+        let first = bus.publisher();
+        let second = bus.publisher();
+        let mut subscriber = bus.subscribe::<u32>();
+
+        second.publish(1u32);
+        bus.flush();
+        first.publish(2u32);
+        bus.flush();
+
+        assert_eq!(subscriber.drain_events(), vec!(1, 2));
+    }
+
+    #[test]
+    fn window_subscribers_only_see_their_window() {
+        let bus = EventBus::new();
+        let publisher = bus.publisher();
+        let mut all = bus.subscribe::<u32>();
+        let mut window = bus.subscribe_window::<u32>(WindowId(1));
+
+        publisher.publish_window(WindowId(2), 1u32);
+        publisher.publish_window(WindowId(1), 2u32);
+        publisher.publish(3u32);
+        bus.flush();
+
+        assert_eq!(all.drain_events(), vec!(1, 2, 3));
+        assert_eq!(window.drain_events(), vec!(2, 3));
+    }
+
+    #[test]
+    fn topics_are_kept_apart() {
+        let bus = EventBus::new();
+        let publisher = bus.publisher();
+        let mut numbers = bus.subscribe::<u32>();
+        let mut names = bus.subscribe::<String>();
+
+        publisher.publish(1u32);
+        publisher.publish("one".to_string());
+        bus.flush();
+
+        assert_eq!(numbers.drain_events(), vec!(1));
+        assert_eq!(names.drain_events(), vec!("one".to_string()));
+    }
+
+    #[test]
+    fn late_subscribers_miss_earlier_events() {
+        let bus = EventBus::new();
+        let publisher = bus.publisher();
+
+        publisher.publish(1u32);
+        bus.flush();
+
+        let mut subscriber = bus.subscribe::<u32>();
+        publisher.publish(2u32);
+        bus.flush();
+
+        assert_eq!(subscriber.drain_events(), vec!(2));
+    }
+
+    #[test]
+    fn dropped_subscribers_stop_receiving() {
+        let bus = EventBus::new();
+        let publisher = bus.publisher();
+        let subscriber = bus.subscribe::<u32>();
+        let id = subscriber.get_id();
+
+        drop(subscriber);
+        publisher.publish(1u32);
+        bus.flush();
+
+        let mut subscriber = bus.subscribe::<u32>();
+        assert!(subscriber.get_id() != id);
+        assert!(subscriber.drain_events().is_empty());
+    }
+}
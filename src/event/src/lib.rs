@@ -1,9 +1,15 @@
 extern crate utils;
 
+use std::error::{Error};
+use std::fmt;
 use std::sync::mpsc::{Sender, Receiver, channel, TryRecvError};
 
 use utils::{WindowId};
 
+pub mod bus;
+
+pub use bus::{Envelope, EventBus, Publisher, PublisherId, Subscriber, SubscriberId};
+
 pub type WindowedEvent<T> = (WindowId, T);
 
 pub type TwoWayChannel<T, F> = (FrontChannel<T, F>, BackChannel<T, F>);
@@ -18,6 +24,27 @@ pub fn two_way_channel<T, F>() -> TwoWayChannel<T, F> {
     )
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventError {
+    SendDisconnected,
+    RecvDisconnected,
+}
+
+impl fmt::Display for EventError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.description())
+    }
+}
+
+impl Error for EventError {
+    fn description(&self) -> &str {
+        match *self {
+            EventError::SendDisconnected => "the receiving end of the channel was disconnected",
+            EventError::RecvDisconnected => "the sending end of the channel was disconnected",
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct FrontChannel<T, F> {
     send_to: Sender<T>,
@@ -32,19 +59,19 @@ impl<T, F> FrontChannel<T, F> {
         }
     }
 
-    pub fn send_to(&mut self, event: T) {
-        self.send_to.send(event).unwrap_or_else(|err| panic!("failed to send to because: {}", err));
+    pub fn send_to(&mut self, event: T) -> Result<(), EventError> {
+        self.send_to.send(event).map_err(|_| EventError::SendDisconnected)
     }
 
-    pub fn recv_from(&mut self) -> F {
-        self.recv_from.recv().unwrap_or_else(|err| panic!("recv from error: {}", err))
+    pub fn recv_from(&mut self) -> Result<F, EventError> {
+        self.recv_from.recv().map_err(|_| EventError::RecvDisconnected)
     }
 
-    pub fn try_recv_from(&mut self) -> Option<F> {
+    pub fn try_recv_from(&mut self) -> Result<Option<F>, EventError> {
         match self.recv_from.try_recv() {
-            Ok(event) => Some(event),
-            Err(TryRecvError::Empty) => None,
-            Err(TryRecvError::Disconnected) => panic!("try recv from was disconnected"),
+            Ok(event) => Ok(Some(event)),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => Err(EventError::RecvDisconnected),
         }
     }
 }
@@ -63,19 +90,19 @@ impl<T, F> BackChannel<T, F> {
         }
     }
 
-    pub fn send_from(&mut self, event: F) {
-        self.send_from.send(event).unwrap_or_else(|err| panic!("failed to send from because: {}", err));
+    pub fn send_from(&mut self, event: F) -> Result<(), EventError> {
+        self.send_from.send(event).map_err(|_| EventError::SendDisconnected)
     }
 
-    pub fn recv_to(&mut self) -> T {
-        self.recv_to.recv().unwrap_or_else(|err| panic!("recv to error: {}", err))
+    pub fn recv_to(&mut self) -> Result<T, EventError> {
+        self.recv_to.recv().map_err(|_| EventError::RecvDisconnected)
     }
 
-    pub fn try_recv_to(&mut self) -> Option<T> {
+    pub fn try_recv_to(&mut self) -> Result<Option<T>, EventError> {
         match self.recv_to.try_recv() {
-            Ok(event) => Some(event),
-            Err(TryRecvError::Empty) => None,
-            Err(TryRecvError::Disconnected) => panic!("try recv to was disconnected"),
+            Ok(event) => Ok(Some(event)),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => Err(EventError::RecvDisconnected),
         }
     }
 }
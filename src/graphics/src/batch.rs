@@ -0,0 +1,162 @@
+use std::path::{Path};
+use std::sync::{Arc};
+
+use gfx::{Slice, PipelineState, Encoder};
+use gfx::state::{MASK_ALL};
+
+use pipeline::{RenderState, TextureData};
+use ::{Resources, CommandBuffer, Shaders, GraphicsError, ScissorRect};
+
+pub const DEFAULT_BATCH_CAPACITY: usize = 4096;
+
+pub fn make_batch_shaders(shaders_path: &Path) -> Result<Shaders, GraphicsError> {
+    warn!("Making Batch Shaders");
+    Shaders::new(shaders_path, "sprite_batch_150_v.glsl", "sprite_batch_150_f.glsl")
+}
+
+gfx_defines! {
+    vertex BatchVertex {
+        pos: [f32; 3] = "a_Pos",
+        uv: [f32; 2] = "a_Uv",
+        tint: [f32; 4] = "a_Tint",
+    }
+
+    constant BatchData {
+        view: [[f32; 4]; 4] = "u_View",
+        proj: [[f32; 4]; 4] = "u_Proj",
+    }
+
+    pipeline batch_pipe {
+        vbuf: gfx::VertexBuffer<BatchVertex> = (),
+
+        batch_data: gfx::ConstantBuffer<BatchData> = "b_BatchData",
+
+        spritesheet: gfx::TextureSampler<[f32; 4]> = "t_Texture",
+
+        out_color: ::gfx::BlendTarget<::ColorFormat> = ("Target0", gfx::state::MASK_ALL, gfx::preset::blend::ALPHA),
+        out_depth: gfx::DepthTarget<::DepthFormat> = ::gfx::preset::depth::LESS_EQUAL_WRITE,
+        scissor: gfx::Scissor = (),
+    }
+}
+
+pub fn make_batch_pipe_init(render_state: RenderState) -> batch_pipe::Init<'static> {
+    batch_pipe::Init {
+        out_color: ("Target0", MASK_ALL, render_state.get_blend()),
+        out_depth: render_state.get_depth(),
+        .. batch_pipe::new()
+    }
+}
+
+// Corners of the unit quad and their uvs, matching art::make_square_render
+const QUAD_CORNERS: [([f32; 2], [f32; 2]); 4] = [
+    ([0.0, 0.0], [1.0, 1.0]),
+    ([0.0, 1.0], [1.0, 0.0]),
+    ([1.0, 1.0], [0.0, 0.0]),
+    ([1.0, 0.0], [0.0, 1.0]),
+];
+
+const QUAD_INDICES: [u32; 6] = [0, 3, 2, 2, 1, 0];
+
+pub fn make_batch_indices(capacity: usize) -> Vec<u32> {
+    let mut indices = Vec::with_capacity(capacity * QUAD_INDICES.len());
+
+    for quad in 0..capacity as u32 {
+        for index in QUAD_INDICES.iter() {
+            indices.push(quad * 4 + index);
+        }
+    }
+
+    indices
+}
+
+pub struct SpriteBatch {
+    slice: Slice<Resources>,
+    pso: Arc<PipelineState<Resources, batch_pipe::Meta>>,
+    data: batch_pipe::Data<Resources>,
+    vertices: Vec<BatchVertex>,
+    capacity: usize,
+    texel_inset: f32,
+}
+
+impl SpriteBatch {
+    pub fn new(
+        slice: Slice<Resources>,
+        pso: Arc<PipelineState<Resources, batch_pipe::Meta>>,
+        data: batch_pipe::Data<Resources>,
+        capacity: usize,
+        texel_inset: f32,
+    ) -> SpriteBatch {
+        SpriteBatch {
+            slice: slice,
+            pso: pso,
+            data: data,
+            vertices: Vec::with_capacity(capacity * 4),
+            capacity: capacity,
+            texel_inset: texel_inset,
+        }
+    }
+
+    pub fn get_data(&self) -> &batch_pipe::Data<Resources> {
+        &self.data
+    }
+
+    pub fn get_mut_data(&mut self) -> &mut batch_pipe::Data<Resources> {
+        &mut self.data
+    }
+
+    pub fn len(&self) -> usize {
+        self.vertices.len() / 4
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.vertices.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.vertices.clear();
+    }
+
+    pub fn push(&mut self, model: [[f32; 4]; 4], texture_data: &TextureData) {
+        let rect = texture_data.clone().with_inset(self.texel_inset).spritesheet_rect;
+        let size = texture_data.spritesheet_size;
+
+        let min = [rect[0] / size[0], rect[1] / size[1]];
+        let max = [(rect[0] + rect[2]) / size[0], (rect[1] + rect[3]) / size[1]];
+
+        for &(corner, uv) in QUAD_CORNERS.iter() {
+            let u = if texture_data.mirror_x { 1.0 - uv[0] } else { uv[0] };
+            let v = if texture_data.mirror_y { 1.0 - uv[1] } else { uv[1] };
+
+            // Column major, same layout the per entity path uploads as u_Model
+            let transform = |row: usize| model[0][row] * corner[0] + model[1][row] * corner[1] + model[3][row];
+
+            self.vertices.push(BatchVertex {
+                pos: [transform(0), transform(1), transform(2)],
+                uv: [u * (max[0] - min[0]) + min[0], v * (max[1] - min[1]) + min[1]],
+                tint: texture_data.tint,
+            });
+        }
+    }
+
+    pub fn encode(&mut self, encoder: &mut Encoder<Resources, CommandBuffer>, view: [[f32; 4]; 4], proj: [[f32; 4]; 4], scissor: ScissorRect) -> Result<(), GraphicsError> {
+        if self.vertices.is_empty() {
+            return Ok(());
+        }
+
+        self.data.scissor = scissor;
+        encoder.update_constant_buffer(&self.data.batch_data, &BatchData {
+            view: view,
+            proj: proj,
+        });
+
+        for chunk in self.vertices.chunks(self.capacity * 4) {
+            try!(encoder.update_buffer(&self.data.vbuf, chunk, 0).map_err(|err| GraphicsError::Pipeline(format!("{:?}", err))));
+
+            self.slice.end = (chunk.len() / 4 * QUAD_INDICES.len()) as u32;
+            encoder.draw(&self.slice, &self.pso, &self.data);
+        }
+
+        self.vertices.clear();
+        Ok(())
+    }
+}
@@ -0,0 +1,89 @@
+use std::any::{Any, TypeId};
+use std::collections::{HashMap};
+use std::sync::{Arc};
+
+use gfx::{PipelineState, Primitive};
+use gfx::handle::{Program, Sampler};
+use gfx::pso::{PipelineInit};
+use gfx::state::{Rasterizer};
+use gfx::tex::{SamplerInfo};
+use gfx::traits::{Factory, FactoryExt};
+
+use ::{GlFactory, Resources, Shaders, GraphicsError, RenderState};
+
+pub type ShaderKey = (&'static str, &'static str);
+
+type PipelineKey = (ShaderKey, Primitive, Rasterizer, RenderState, TypeId);
+
+// Shader programs, pipeline states and samplers belong to the factory that made them,
+// so one cache lives beside each window's factory
+pub struct PipelineCache {
+    programs: HashMap<ShaderKey, Program<Resources>>,
+    pipelines: HashMap<PipelineKey, Box<Any>>,
+    samplers: HashMap<SamplerInfo, Sampler<Resources>>,
+}
+
+impl PipelineCache {
+    pub fn new() -> PipelineCache {
+        PipelineCache {
+            programs: HashMap::new(),
+            pipelines: HashMap::new(),
+            samplers: HashMap::new(),
+        }
+    }
+
+    pub fn get_program(&mut self, factory: &mut GlFactory, shaders: &Shaders) -> Result<Program<Resources>, GraphicsError> {
+        let key = shaders.get_key();
+
+        if let Some(program) = self.programs.get(&key) {
+            return Ok(program.clone());
+        }
+
+        warn!("Creating Program for {:?}", key);
+        let shader_set = try!(factory.create_shader_set(shaders.get_vertex_shader(), shaders.get_fragment_shader()).map_err(|err| GraphicsError::Shader(format!("{:?}", err))));
+        let program = try!(factory.create_program(&shader_set).map_err(|err| GraphicsError::Shader(format!("{:?}", err))));
+
+        self.programs.insert(key, program.clone());
+
+        Ok(program)
+    }
+
+    pub fn get_pipeline<I>(&mut self,
+        factory: &mut GlFactory,
+        shaders: &Shaders,
+        primitive: Primitive,
+        rasterizer: Rasterizer,
+        render_state: RenderState,
+        make_init: fn(RenderState) -> I
+    ) -> Result<Arc<PipelineState<Resources, I::Meta>>, GraphicsError>
+    where I: PipelineInit, I::Meta: 'static {
+        // Each pipeline layout has one init builder and the init is only built from the key,
+        // so the layout's TypeId and the render state are all it takes to tell pipelines apart
+        let key = (shaders.get_key(), primitive, rasterizer, render_state, TypeId::of::<I::Meta>());
+
+        if let Some(pso) = self.pipelines.get(&key).and_then(|pso| pso.downcast_ref::<Arc<PipelineState<Resources, I::Meta>>>()) {
+            return Ok(pso.clone());
+        }
+
+        let program = try!(self.get_program(factory, shaders));
+
+        warn!("Creating Pipeline for {:?}", key.0);
+        let pso = Arc::new(try!(factory.create_pipeline_from_program(
+            &program,
+            primitive,
+            rasterizer,
+            make_init(render_state)
+        ).map_err(|err| GraphicsError::Pipeline(format!("{:?}", err)))));
+
+        self.pipelines.insert(key, Box::new(pso.clone()));
+
+        Ok(pso)
+    }
+
+    pub fn get_sampler(&mut self, factory: &mut GlFactory, sampler_info: SamplerInfo) -> Sampler<Resources> {
+        self.samplers.entry(sampler_info).or_insert_with(|| {
+            warn!("Creating Sampler for {:?}", sampler_info);
+            factory.create_sampler(sampler_info)
+        }).clone()
+    }
+}
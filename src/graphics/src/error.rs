@@ -0,0 +1,50 @@
+use std::error::{Error};
+use std::fmt;
+use std::io;
+use std::path::{PathBuf};
+
+use image::{ImageError};
+
+#[derive(Debug)]
+pub enum GraphicsError {
+    Io(PathBuf, io::Error),
+    Image(PathBuf, ImageError),
+    Texture(PathBuf, String),
+    Shader(String),
+    Pipeline(String),
+    Sdl(String),
+}
+
+impl fmt::Display for GraphicsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            GraphicsError::Io(ref path, ref err) => write!(f, "unable to read {:?}: {}", path, err),
+            GraphicsError::Image(ref path, ref err) => write!(f, "unable to load image {:?}: {}", path, err),
+            GraphicsError::Texture(ref path, ref err) => write!(f, "unable to create texture from {:?}: {}", path, err),
+            GraphicsError::Shader(ref err) => write!(f, "unable to build shader program: {}", err),
+            GraphicsError::Pipeline(ref err) => write!(f, "unable to create pipeline: {}", err),
+            GraphicsError::Sdl(ref err) => write!(f, "sdl error: {}", err),
+        }
+    }
+}
+
+impl Error for GraphicsError {
+    fn description(&self) -> &str {
+        match *self {
+            GraphicsError::Io(..) => "io error",
+            GraphicsError::Image(..) => "image error",
+            GraphicsError::Texture(..) => "texture error",
+            GraphicsError::Shader(..) => "shader error",
+            GraphicsError::Pipeline(..) => "pipeline error",
+            GraphicsError::Sdl(..) => "sdl error",
+        }
+    }
+
+    fn cause(&self) -> Option<&Error> {
+        match *self {
+            GraphicsError::Io(_, ref err) => Some(err),
+            GraphicsError::Image(_, ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
@@ -12,13 +12,21 @@ pub use dependencies::{find_folder, gfx_device_gl, gfx_window_glutin, glutin, sd
 use gfx::handle::{RenderTargetView, DepthStencilView};
 use gfx::format::{Srgba8, DepthStencil};
 
+pub mod batch;
+pub mod cache;
+pub mod error;
+pub mod material;
 pub mod pipeline;
 pub mod shaders;
 pub mod textures;
 
-pub use self::pipeline::{pipe, Vertex, Bundle, Index, Packet, make_shaders, ProjectionData, TextureData};
+pub use self::batch::{batch_pipe, BatchData, BatchVertex, SpriteBatch, DEFAULT_BATCH_CAPACITY, make_batch_indices, make_batch_pipe_init, make_batch_shaders};
+pub use self::cache::{PipelineCache, ShaderKey};
+pub use self::error::{GraphicsError};
+pub use self::material::{Materials, get_shader_names, make_materials};
+pub use self::pipeline::{pipe, Vertex, Bundle, Index, Packet, MaterialData, ProjectionData, RenderState, TextureData, make_pipe_init};
 pub use self::shaders::{Shaders};
-pub use self::textures::{load_texture};
+pub use self::textures::{TextureSettings, load_texture};
 
 pub type GlDevice = gfx_device_gl::Device;
 pub type GlFactory = gfx_device_gl::Factory;
@@ -30,9 +38,10 @@ pub type OutColor = RenderTargetView<Resources, ColorFormat>;
 pub type OutDepth = DepthStencilView<Resources, DepthFormat>;
 pub type Encoder = gfx::Encoder<Resources, CommandBuffer>;
 pub type RlTexture = gfx::handle::ShaderResourceView<Resources, [f32; 4]>;
+pub type ScissorRect = gfx::target::Rect;
 
 pub use gfx::traits::{Factory, FactoryExt};
-pub use gfx::{Device, Primitive};
+pub use gfx::{Device, Primitive, Slice, IntoIndexBuffer, Bind, BufferRole};
 pub use gfx::state::{Rasterizer};
 pub use gfx::tex::{FilterMethod, SamplerInfo, WrapMode};
 
@@ -46,16 +55,14 @@ pub use gfx::tex::{FilterMethod, SamplerInfo, WrapMode};
 // );
 
 pub mod rl_sdl2 {
-    use std::collections::{HashMap};
-
     use utils::{WindowId};
 
     use sdl2::{self, Sdl};
-    use sdl2::video::{Window, GLContext};
+    use sdl2::video::{Window, GLContext, VideoSubsystem};
 
     use gfx_window_sdl::{self};
 
-    use ::{OutColor, OutDepth, GlFactory, Encoder, GlDevice};
+    use ::{OutColor, OutDepth, GlFactory, Encoder, GlDevice, GraphicsError, PipelineCache};
 
     pub type WindowSettings<'a> = (&'a str, u32, u32);
 
@@ -81,6 +88,7 @@ pub mod rl_sdl2 {
         out_color: OutColor,
         out_depth: OutDepth,
         factory: GlFactory,
+        pipeline_cache: PipelineCache,
         window: Window,
         device: GlDevice,
         gl_context: GLContext,
@@ -97,6 +105,7 @@ pub mod rl_sdl2 {
                 out_color: out_color,
                 out_depth: out_depth,
                 factory: factory,
+                pipeline_cache: PipelineCache::new(),
                 window: window,
                 device: device,
                 gl_context: gl_context,
@@ -119,6 +128,10 @@ pub mod rl_sdl2 {
             &mut self.factory
         }
 
+        pub fn get_mut_factory_and_cache(&mut self) -> (&mut GlFactory, &mut PipelineCache) {
+            (&mut self.factory, &mut self.pipeline_cache)
+        }
+
         pub fn get_window(&self) -> &Window {
             &self.window
         }
@@ -138,39 +151,48 @@ pub mod rl_sdl2 {
         pub fn get_gl_context(&self) -> &GLContext {
             &self.gl_context
         }
+
+        pub fn update_views(&mut self) {
+            gfx_window_sdl::update_views(&self.window, &mut self.out_color, &mut self.out_depth);
+        }
     }
 
-    pub fn build_graphics_sdl(window_settings_vec: Vec<WindowSettings>) -> (Sdl, HashMap<WindowId, SdlGraphicEncoder>)  {
-        let sdl = sdl2::init().unwrap_or_else(|err| panic!("Error while sdl2::init: {:?}", err));
+    pub fn build_graphics_sdl(window_settings_vec: Vec<WindowSettings>) -> Result<(Sdl, Vec<(WindowId, SdlGraphicEncoder)>), GraphicsError> {
+        let sdl = try!(sdl2::init().map_err(GraphicsError::Sdl));
 
-        let video = sdl.video().unwrap_or_else(|err| panic!("Error while making sdl.video(): {:?}", err));
+        let video = try!(sdl.video().map_err(GraphicsError::Sdl));
         let gl_attr = video.gl_attr();
         gl_attr.set_context_version(3, 2);
         gl_attr.set_context_profile(sdl2::video::GLProfile::Core);
 
-        let mut out = HashMap::new();
+        let mut out = vec!();
 
         for window_settings in window_settings_vec {
-            let (title, width, height) = window_settings;
-            let mut builder = video.window(title, width, height);
-            let (window, context, device, mut factory, out_color, out_depth) = gfx_window_sdl::init(&mut builder); //_ = glcontext
-
-            let encoder = factory.create_command_buffer().into();
-
-            out.insert(WindowId(window.id()), SdlGraphicEncoder::new(
-                encoder,
-                SdlGraphic::new(
-                    out_color,
-                    out_depth,
-                    factory,
-                    window,
-                    device,
-                    context
-                )
-            ));
+            out.push(try!(build_window_sdl(&video, window_settings)));
         }
 
-        (sdl, out)
+        Ok((sdl, out))
+    }
+
+    pub fn build_window_sdl(video: &VideoSubsystem, window_settings: WindowSettings) -> Result<(WindowId, SdlGraphicEncoder), GraphicsError> {
+        let (title, width, height) = window_settings;
+        let mut builder = video.window(title, width, height);
+        builder.resizable();
+        let (window, context, device, mut factory, out_color, out_depth) = gfx_window_sdl::init(&mut builder); //_ = glcontext
+
+        let encoder = factory.create_command_buffer().into();
+
+        Ok((WindowId(window.id()), SdlGraphicEncoder::new(
+            encoder,
+            SdlGraphic::new(
+                out_color,
+                out_depth,
+                factory,
+                window,
+                device,
+                context
+            )
+        )))
     }
 }
 
@@ -0,0 +1,42 @@
+use std::path::{Path};
+
+use components::{Material, MATERIALS};
+
+use ::{Shaders, GraphicsError};
+
+pub fn get_shader_names(material: Material) -> (&'static str, &'static str) {
+    match material {
+        Material::Flat => ("spritesheet_150_v.glsl", "flat_150_f.glsl"),
+        Material::Textured => ("spritesheet_150_v.glsl", "spritesheet_150_f.glsl"),
+        Material::PaletteSwap => ("spritesheet_150_v.glsl", "palette_swap_150_f.glsl"),
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Materials {
+    shaders: Vec<(Material, Shaders)>,
+}
+
+impl Materials {
+    pub fn get_shaders(&self, material: Material) -> Option<&Shaders> {
+        self.shaders.iter().find(|&&(other, _)| other == material).map(|&(_, ref shaders)| shaders)
+    }
+
+    pub fn iter(&self) -> ::std::slice::Iter<(Material, Shaders)> {
+        self.shaders.iter()
+    }
+}
+
+pub fn make_materials(shaders_path: &Path) -> Result<Materials, GraphicsError> {
+    let mut shaders = vec!();
+
+    for material in MATERIALS {
+        warn!("Making Shaders for Material: {}", material.get_name());
+        let (vertex_name, fragment_name) = get_shader_names(*material);
+        shaders.push((*material, try!(Shaders::new(shaders_path, vertex_name, fragment_name))));
+    }
+
+    Ok(Materials {
+        shaders: shaders,
+    })
+}
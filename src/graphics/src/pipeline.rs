@@ -1,14 +1,15 @@
+use std::collections::{HashMap};
+use std::sync::{Arc};
+
 use gfx::{Slice, PipelineState, Encoder};
-use gfx::state::{Rasterizer};
+use gfx::preset::{blend, depth};
+use gfx::state::{Blend, BlendValue, Depth, Equation, Factor, Rasterizer, MASK_ALL};
 
-use ::{Resources, CommandBuffer, Shaders};
+use components::{BlendMode, Material, Palette, BLEND_MODES};
 
-pub type Index = u32;
+use ::{Resources, CommandBuffer};
 
-pub fn make_shaders() -> Shaders {
-    warn!("Making Shaders");
-    Shaders::new("spritesheet_150_v.glsl", "spritesheet_150_f.glsl")
-}
+pub type Index = u32;
 
 gfx_defines! {
     vertex Vertex {
@@ -24,6 +25,13 @@ gfx_defines! {
         mirror_y: bool = "u_MirrorY",
     }
 
+    constant MaterialData {
+        params: [f32; 4] = "u_MaterialParams",
+        palette_from: [[f32; 4]; 4] = "u_PaletteFrom",
+        palette_to: [[f32; 4]; 4] = "u_PaletteTo",
+        time: f32 = "u_Time",
+    }
+
     constant ProjectionData {
         model: [[f32; 4]; 4] = "u_Model",
         view: [[f32; 4]; 4] = "u_View",
@@ -39,8 +47,11 @@ gfx_defines! {
 
         texture_data: gfx::ConstantBuffer<TextureData> = "b_TextureData",
 
+        material_data: gfx::ConstantBuffer<MaterialData> = "b_MaterialData",
+
         out_color: ::gfx::BlendTarget<::ColorFormat> = ("Target0", gfx::state::MASK_ALL, gfx::preset::blend::ALPHA),
         out_depth: gfx::DepthTarget<::DepthFormat> = ::gfx::preset::depth::LESS_EQUAL_WRITE,
+        scissor: gfx::Scissor = (),
     }
 }
 
@@ -53,25 +64,122 @@ impl Vertex {
     }
 }
 
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
+pub struct RenderState {
+    blend_mode: BlendMode,
+    depth_write: bool,
+}
+
+impl RenderState {
+    pub fn new(blend_mode: BlendMode, depth_write: bool) -> RenderState {
+        RenderState {
+            blend_mode: blend_mode,
+            depth_write: depth_write,
+        }
+    }
+
+    pub fn new_default() -> RenderState {
+        RenderState::new(BlendMode::Alpha, true)
+    }
+
+    pub fn all() -> Vec<RenderState> {
+        let mut states = vec!();
+
+        for blend_mode in BLEND_MODES {
+            for &depth_write in &[true, false] {
+                states.push(RenderState::new(*blend_mode, depth_write));
+            }
+        }
+
+        states
+    }
+
+    pub fn get_blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
+    pub fn get_depth_write(&self) -> bool {
+        self.depth_write
+    }
+
+    pub fn get_blend(&self) -> Blend {
+        match self.blend_mode {
+            BlendMode::Alpha => blend::ALPHA,
+            BlendMode::Additive => Blend::new(Equation::Add, Factor::ZeroPlus(BlendValue::SourceAlpha), Factor::One),
+            BlendMode::Multiply => blend::MULTIPLY,
+            BlendMode::PremultipliedAlpha => Blend::new(Equation::Add, Factor::One, Factor::OneMinus(BlendValue::SourceAlpha)),
+            BlendMode::Opaque => blend::REPLACE,
+        }
+    }
+
+    pub fn get_depth(&self) -> Depth {
+        if self.depth_write {
+            depth::LESS_EQUAL_WRITE
+        } else {
+            depth::LESS_EQUAL_TEST
+        }
+    }
+}
+
+pub fn make_pipe_init(render_state: RenderState) -> pipe::Init<'static> {
+    pipe::Init {
+        out_color: ("Target0", MASK_ALL, render_state.get_blend()),
+        out_depth: render_state.get_depth(),
+        .. pipe::new()
+    }
+}
+
+impl TextureData {
+    // Pulls the sprite rect in on every side, so filtering never reaches into the neighbouring sprite
+    pub fn with_inset(mut self, inset: f32) -> TextureData {
+        let rect = self.spritesheet_rect;
+        self.spritesheet_rect = [rect[0] + inset, rect[1] + inset, rect[2] - inset * 2.0, rect[3] - inset * 2.0];
+        self
+    }
+}
+
+impl MaterialData {
+    pub fn new(params: [f32; 4], palette: Option<(&'static Palette, &'static Palette)>, time: f32) -> MaterialData {
+        let (palette_from, palette_to) = match palette {
+            Some((from, to)) => (from.clone(), to.clone()),
+            None => ([[0.0; 4]; 4], [[0.0; 4]; 4]),
+        };
+
+        MaterialData {
+            params: params,
+            palette_from: palette_from,
+            palette_to: palette_to,
+            time: time,
+        }
+    }
+}
+
 pub struct Bundle {
     slice: Slice<Resources>,
-    pso: PipelineState<Resources, pipe::Meta>,
+    psos: HashMap<(Material, RenderState), Arc<PipelineState<Resources, pipe::Meta>>>,
     data: pipe::Data<Resources>,
+    texel_inset: f32,
 }
 
 impl Bundle {
     pub fn new(
         slice: Slice<Resources>,
-        pso: PipelineState<Resources, pipe::Meta>,
+        psos: HashMap<(Material, RenderState), Arc<PipelineState<Resources, pipe::Meta>>>,
         data: pipe::Data<Resources>,
+        texel_inset: f32,
     ) -> Bundle {
         Bundle {
             slice: slice,
-            pso: pso,
+            psos: psos,
             data: data,
+            texel_inset: texel_inset,
         }
     }
 
+    pub fn get_texel_inset(&self) -> f32 {
+        self.texel_inset
+    }
+
     pub fn get_data(&self) -> &pipe::Data<Resources> {
         &self.data
     }
@@ -80,8 +188,15 @@ impl Bundle {
         &mut self.data
     }
 
-    pub fn encode(&self, encoder: &mut Encoder<Resources, CommandBuffer>) {
-        encoder.draw(&self.slice, &self.pso, &self.data);
+    pub fn has_pipeline(&self, material: Material, render_state: RenderState) -> bool {
+        self.psos.contains_key(&(material, render_state))
+    }
+
+    pub fn encode(&self, encoder: &mut Encoder<Resources, CommandBuffer>, material: Material, render_state: RenderState) {
+        match self.psos.get(&(material, render_state)) {
+            Some(pso) => encoder.draw(&self.slice, pso, &self.data),
+            None => warn!("Bundle has no pipeline for material: {}, {:?}", material.get_name(), render_state),
+        }
     }
 }
 
@@ -1,55 +1,32 @@
 use std::io::{BufReader, Read};
 use std::fs::{File};
+use std::path::{Path};
 
-use ::find_folder::{Search};
+use ::{GraphicsError};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Shaders {
+    vertex_name: &'static str,
+    fragment_name: &'static str,
     vertex: Vec<u8>,
     fragment: Vec<u8>,
 }
 
 impl Shaders {
-    pub fn new(vertex_name: &'static str, fragment_name: &'static str) -> Shaders {
-        let shaders_path = match Search::ParentsThenKids(3, 3).for_folder("shaders") {
-            Ok(shaders_path) => shaders_path,
-            Err(err) => panic!("find folder shaders error: {}", err),
-        };
-
-        let mut vertex_path = shaders_path.clone();
-        let mut fragment_path = shaders_path.clone();
-
-        vertex_path.push(vertex_name);
-        fragment_path.push(fragment_name);
-
-        let vertex_file = match File::open(vertex_path) {
-            Ok(file) => file,
-            Err(err) => panic!("vertex file open err: {}", err),
-        };
-        let fragment_file = match File::open(fragment_path) {
-            Ok(file) => file,
-            Err(err) => panic!("fragment file open err: {}", err),
-        };
-
-        let mut vertex_reader = BufReader::new(vertex_file);
-        let mut fragment_reader = BufReader::new(fragment_file);
-
-        let mut vertex_buffer = vec!();
-        let mut fragment_buffer = vec!();
-
-        match vertex_reader.read_to_end(&mut vertex_buffer) {
-            Ok(_) => (),
-            Err(err) => panic!("vertex reader read to end error: {}", err),
-        };
-        match fragment_reader.read_to_end(&mut fragment_buffer) {
-            Ok(_) => (),
-            Err(err) => panic!("fragment reader read to end error: {}", err),
-        };
-
-        Shaders {
-            vertex: vertex_buffer,
-            fragment: fragment_buffer,
-        }
+    pub fn new(shaders_path: &Path, vertex_name: &'static str, fragment_name: &'static str) -> Result<Shaders, GraphicsError> {
+        let vertex_path = shaders_path.join(vertex_name);
+        let fragment_path = shaders_path.join(fragment_name);
+
+        Ok(Shaders {
+            vertex_name: vertex_name,
+            fragment_name: fragment_name,
+            vertex: try!(read_shader(&vertex_path)),
+            fragment: try!(read_shader(&fragment_path)),
+        })
+    }
+
+    pub fn get_key(&self) -> (&'static str, &'static str) {
+        (self.vertex_name, self.fragment_name)
     }
 
     pub fn get_vertex_shader(&self) -> &[u8] {
@@ -60,3 +37,14 @@ impl Shaders {
         self.fragment.as_slice()
     }
 }
+
+fn read_shader(path: &Path) -> Result<Vec<u8>, GraphicsError> {
+    let file = try!(File::open(path).map_err(|err| GraphicsError::Io(path.to_path_buf(), err)));
+
+    let mut reader = BufReader::new(file);
+    let mut buffer = vec!();
+
+    try!(reader.read_to_end(&mut buffer).map_err(|err| GraphicsError::Io(path.to_path_buf(), err)));
+
+    Ok(buffer)
+}
@@ -0,0 +1,22 @@
+#version 150 core
+
+uniform b_TextureData {
+    vec4 u_Tint;
+    vec4 u_SpritesheetRect;
+    vec2 u_SpritesheetSize;
+    bool u_MirrorX;
+    bool u_MirrorY;
+};
+
+uniform b_MaterialData {
+    vec4 u_MaterialParams;
+    mat4 u_PaletteFrom;
+    mat4 u_PaletteTo;
+    float u_Time;
+};
+
+out vec4 Target0;
+
+void main() {
+    Target0 = vec4(mix(u_Tint.rgb, u_MaterialParams.rgb, u_MaterialParams.a), u_Tint.a);
+}
@@ -0,0 +1,41 @@
+#version 150 core
+
+in vec2 v_Uv;
+
+uniform sampler2D t_Texture;
+
+uniform b_TextureData {
+    vec4 u_Tint;
+    vec4 u_SpritesheetRect;
+    vec2 u_SpritesheetSize;
+    bool u_MirrorX;
+    bool u_MirrorY;
+};
+
+uniform b_MaterialData {
+    vec4 u_MaterialParams;
+    mat4 u_PaletteFrom;
+    mat4 u_PaletteTo;
+    float u_Time;
+};
+
+out vec4 Target0;
+
+const float MATCH_DISTANCE = 0.01;
+
+void main() {
+    vec4 scaledRect = vec4(u_SpritesheetRect.xy, u_SpritesheetRect.zw + u_SpritesheetRect.xy) / u_SpritesheetSize.xyxy;
+
+    vec2 uv = vec2(u_MirrorX ? 1.0 - v_Uv.x : v_Uv.x, u_MirrorY ? 1.0 - v_Uv.y : v_Uv.y);
+    vec4 color = texture(t_Texture, uv * (scaledRect.zw - scaledRect.xy) + scaledRect.xy);
+
+    for (int i = 0; i < 4; i++) {
+        if (distance(color.rgb, u_PaletteFrom[i].rgb) < MATCH_DISTANCE) {
+            color.rgb = u_PaletteTo[i].rgb;
+            break;
+        }
+    }
+
+    Target0 = color * u_Tint;
+    Target0.rgb = mix(Target0.rgb, u_MaterialParams.rgb, u_MaterialParams.a);
+}
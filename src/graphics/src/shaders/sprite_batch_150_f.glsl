@@ -0,0 +1,12 @@
+#version 150 core
+
+in vec2 v_Uv;
+in vec4 v_Tint;
+
+uniform sampler2D t_Texture;
+
+out vec4 Target0;
+
+void main() {
+    Target0 = texture(t_Texture, v_Uv) * v_Tint;
+}
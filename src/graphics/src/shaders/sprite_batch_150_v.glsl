@@ -0,0 +1,19 @@
+#version 150 core
+
+in vec3 a_Pos;
+in vec2 a_Uv;
+in vec4 a_Tint;
+
+uniform b_BatchData {
+    mat4 u_View;
+    mat4 u_Proj;
+};
+
+out vec2 v_Uv;
+out vec4 v_Tint;
+
+void main() {
+    v_Uv = a_Uv;
+    v_Tint = a_Tint;
+    gl_Position = u_Proj * u_View * vec4(a_Pos, 1.0);
+}
@@ -12,6 +12,13 @@ uniform b_TextureData {
     bool u_MirrorY;
 };
 
+uniform b_MaterialData {
+    vec4 u_MaterialParams;
+    mat4 u_PaletteFrom;
+    mat4 u_PaletteTo;
+    float u_Time;
+};
+
 out vec4 Target0;
 
 void main() {
@@ -30,4 +37,7 @@ void main() {
             Target0 = texture(t_Texture, v_Uv * (scaledRect.zw - scaledRect.xy) + scaledRect.xy) * u_Tint;
         }
     }
+
+    // Flash towards the params color by its alpha, for hit effects
+    Target0.rgb = mix(Target0.rgb, u_MaterialParams.rgb, u_MaterialParams.a);
 }
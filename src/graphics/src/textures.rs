@@ -2,23 +2,108 @@ use std::path::{Path};
 
 use gfx::{Factory};
 use gfx::handle::{ShaderResourceView};
-use gfx::tex::{Size, AaMode, Kind};
+use gfx::tex::{Size, AaMode, Kind, FilterMethod, SamplerInfo, WrapMode};
 
 use image;
 
-use ::{Resources, GlFactory, ColorFormat};
+use ::{Resources, GlFactory, ColorFormat, GraphicsError};
 
-pub fn load_texture<P>(factory: &mut GlFactory, path: P) -> ShaderResourceView<Resources, [f32; 4]>
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextureSettings {
+    filter: FilterMethod,
+    wrap: WrapMode,
+    border: [f32; 4],
+    half_texel_inset: bool,
+}
+
+impl TextureSettings {
+    pub fn new(filter: FilterMethod, wrap: WrapMode) -> TextureSettings {
+        TextureSettings {
+            filter: filter,
+            wrap: wrap,
+            border: [0.0, 0.0, 0.0, 0.0],
+            half_texel_inset: false,
+        }
+    }
+
+    // Pixel art sheets want hard texels and no wrapping into the opposite edge,
+    // sheets that bleed at their sprite edges opt into the half texel inset
+    pub fn new_sprite_sheet() -> TextureSettings {
+        TextureSettings::new(FilterMethod::Scale, WrapMode::Clamp)
+    }
+
+    pub fn with_border(mut self, border: [f32; 4]) -> TextureSettings {
+        self.border = border;
+        self
+    }
+
+    pub fn with_half_texel_inset(mut self, half_texel_inset: bool) -> TextureSettings {
+        self.half_texel_inset = half_texel_inset;
+        self
+    }
+
+    pub fn get_filter(&self) -> FilterMethod {
+        self.filter
+    }
+
+    pub fn get_wrap(&self) -> WrapMode {
+        self.wrap
+    }
+
+    pub fn get_border(&self) -> [f32; 4] {
+        self.border
+    }
+
+    pub fn get_half_texel_inset(&self) -> bool {
+        self.half_texel_inset
+    }
+
+    pub fn get_texel_inset(&self) -> f32 {
+        if self.half_texel_inset {
+            0.5
+        } else {
+            0.0
+        }
+    }
+
+    pub fn get_sampler_info(&self) -> SamplerInfo {
+        let mut sampler_info = SamplerInfo::new(self.filter, self.wrap);
+        sampler_info.border = self.border.into();
+        sampler_info
+    }
+}
+
+pub fn load_texture<P>(factory: &mut GlFactory, path: P) -> Result<ShaderResourceView<Resources, [f32; 4]>, GraphicsError>
 where P: AsRef<Path> {
-    let image = match image::open(path) {
-        Ok(image) => image,
-        Err(err) => panic!("image load error: {}", err),
-    }.to_rgba();
+    let path = path.as_ref();
+    let image = try!(image::open(path).map_err(|err| GraphicsError::Image(path.to_path_buf(), err))).to_rgba();
     let (width, height) = image.dimensions();
     let kind = Kind::D2(width as Size, height as Size, AaMode::Single);
-    let (_, view) = match factory.create_texture_const_u8::<ColorFormat>(kind, &[&image]) {
-        Ok(data) => data,
-        Err(err) => panic!("factory create texture const error: {}", err),
-    };
-    view
+    let (_, view) = try!(factory.create_texture_const_u8::<ColorFormat>(kind, &[&image]).map_err(|err| GraphicsError::Texture(path.to_path_buf(), format!("{:?}", err))));
+    Ok(view)
+}
+
+#[cfg(test)]
+mod tests {
+    use gfx::tex::{FilterMethod, WrapMode};
+
+    use super::{TextureSettings};
+
+    #[test]
+    fn sprite_sheets_default_to_no_inset() {
+        let settings = TextureSettings::new_sprite_sheet();
+
+        assert_eq!(settings.get_filter(), FilterMethod::Scale);
+        assert_eq!(settings.get_wrap(), WrapMode::Clamp);
+        assert!(!settings.get_half_texel_inset());
+        assert_eq!(settings.get_texel_inset(), 0.0);
+    }
+
+    #[test]
+    fn sheets_opt_into_half_texel_inset() {
+        let settings = TextureSettings::new_sprite_sheet().with_half_texel_inset(true);
+
+        assert!(settings.get_half_texel_inset());
+        assert_eq!(settings.get_texel_inset(), 0.5);
+    }
 }
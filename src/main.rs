@@ -4,11 +4,48 @@ extern crate log;
 extern crate core;
 extern crate dependencies;
 
+use std::env;
+use std::io::{self, Write};
+use std::process;
+
 pub use dependencies::{env_logger};
 
+mod cli;
+
+use cli::{Command};
+
 fn main() {
-    env_logger::init().unwrap_or_else(|err| panic!("unable to initiate env logger: {}", err));
+    let (config, options) = match cli::parse_args(env::args().collect()) {
+        Ok(Command::Run(config, options)) => (config, options),
+        Ok(Command::Help(usage)) => {
+            println!("{}", usage);
+            return;
+        },
+        Ok(Command::Version(version)) => {
+            println!("{}", version);
+            return;
+        },
+        Err(err) => {
+            writeln!(io::stderr(), "{}", err).unwrap_or(());
+            process::exit(2);
+        },
+    };
+
+    let mut log_builder = env_logger::LogBuilder::new();
+    match env::var("RUST_LOG") {
+        Ok(rust_log) => log_builder.parse(&rust_log),
+        Err(_) => log_builder.parse(config.get_log_level()),
+    };
+    log_builder.init().unwrap_or_else(|err| panic!("unable to initiate env logger: {}", err));
+
+    warn!("{}", cli::version());
+    if let Err(err) = core::run(config, options) {
+        error!("game exited with an error: {}", err);
+
+        // Each error's Display already includes what caused it
+        writeln!(io::stderr(), "error: {}", err).unwrap_or(());
 
-    core::start();
+        process::exit(1);
+    }
     warn!("game exited successfully");
 }
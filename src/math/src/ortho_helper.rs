@@ -16,8 +16,8 @@ impl OrthographicHelper {
         near: GfxCoord,
         far: GfxCoord
     ) -> OrthographicHelper {
-        let bottom = left * aspect_ratio;
-        let top = right * aspect_ratio;
+        let bottom = left / aspect_ratio;
+        let top = right / aspect_ratio;
 
         OrthographicHelper {
             aspect_ratio: aspect_ratio,
@@ -32,6 +32,10 @@ impl OrthographicHelper {
         }
     }
 
+    pub fn with_aspect_ratio(&self, aspect_ratio: GfxCoord) -> OrthographicHelper {
+        OrthographicHelper::new(aspect_ratio, self.get_left(), self.get_right(), self.get_near(), self.get_far())
+    }
+
     pub fn get_left(&self) -> GfxCoord {
         self.ortho.left
     }
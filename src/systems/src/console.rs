@@ -0,0 +1,424 @@
+use std::collections::{HashSet, VecDeque};
+
+use math::{Point2, Vector3};
+use specs::{System, RunArg};
+
+use components::{Camera, RenderData, RenderId, Transform};
+use utils::{Delta, Step, WindowId};
+
+const MAX_HISTORY: usize = 64;
+const MAX_OUTPUT: usize = 64;
+const MAX_TIME_SCALE: Delta = 16.0;
+
+pub const COMMANDS: &'static [(&'static str, &'static str)] = &[
+    ("help", "help"),
+    ("spawn", "spawn <x> <y>"),
+    ("teleport", "teleport <x> <y>"),
+    ("timescale", "timescale <scale>"),
+    ("layer", "layer <layer> [on|off]"),
+    ("reload", "reload"),
+    ("dump", "dump"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConsoleKey {
+    Submit,
+    Erase,
+    Complete,
+    HistoryPrev,
+    HistoryNext,
+}
+
+impl ConsoleKey {
+    pub fn get_name(&self) -> &'static str {
+        match *self {
+            ConsoleKey::Submit => "submit",
+            ConsoleKey::Erase => "erase",
+            ConsoleKey::Complete => "complete",
+            ConsoleKey::HistoryPrev => "history_prev",
+            ConsoleKey::HistoryNext => "history_next",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<ConsoleKey> {
+        [ConsoleKey::Submit, ConsoleKey::Erase, ConsoleKey::Complete, ConsoleKey::HistoryPrev, ConsoleKey::HistoryNext].iter().find(|key| key.get_name() == name).cloned()
+    }
+}
+
+#[derive(Debug)]
+pub struct Console {
+    window_id: Option<WindowId>,
+    input: String,
+    history: VecDeque<String>,
+    history_cursor: Option<usize>,
+    output: VecDeque<String>,
+    pending: Vec<String>,
+    reload_request: Option<WindowId>,
+    dirty: bool,
+}
+
+impl Console {
+    pub fn new() -> Console {
+        Console {
+            window_id: None,
+            input: String::new(),
+            history: VecDeque::new(),
+            history_cursor: None,
+            output: VecDeque::new(),
+            pending: vec!(),
+            reload_request: None,
+            dirty: false,
+        }
+    }
+
+    pub fn open(&mut self, window_id: WindowId) {
+        self.window_id = Some(window_id);
+        self.dirty = true;
+    }
+
+    pub fn close(&mut self) {
+        self.window_id = None;
+        self.input.clear();
+        self.history_cursor = None;
+        self.dirty = true;
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.window_id.is_some()
+    }
+
+    pub fn get_window_id(&self) -> Option<&WindowId> {
+        self.window_id.as_ref()
+    }
+
+    pub fn insert_text(&mut self, text: &str) {
+        self.input.push_str(text);
+        self.dirty = true;
+    }
+
+    pub fn key(&mut self, key: ConsoleKey) {
+        match key {
+            ConsoleKey::Submit => {
+                let line = self.input.trim().to_string();
+                self.input.clear();
+                self.history_cursor = None;
+
+                if !line.is_empty() {
+                    self.history.push_back(line.clone());
+                    if self.history.len() > MAX_HISTORY {
+                        self.history.pop_front();
+                    }
+                    self.print(format!("> {}", line));
+                    self.pending.push(line);
+                }
+            },
+            ConsoleKey::Erase => {
+                self.input.pop();
+            },
+            ConsoleKey::Complete => self.complete(),
+            ConsoleKey::HistoryPrev => {
+                if self.history.is_empty() {
+                    return;
+                }
+                let cursor = match self.history_cursor {
+                    Some(cursor) if cursor > 0 => cursor - 1,
+                    Some(cursor) => cursor,
+                    None => self.history.len() - 1,
+                };
+                self.history_cursor = Some(cursor);
+                self.input = self.history[cursor].clone();
+            },
+            ConsoleKey::HistoryNext => {
+                match self.history_cursor {
+                    Some(cursor) if cursor + 1 < self.history.len() => {
+                        self.history_cursor = Some(cursor + 1);
+                        self.input = self.history[cursor + 1].clone();
+                    },
+                    Some(_) => {
+                        self.history_cursor = None;
+                        self.input.clear();
+                    },
+                    None => (),
+                }
+            },
+        }
+
+        self.dirty = true;
+    }
+
+    fn complete(&mut self) {
+        if self.input.contains(' ') {
+            return;
+        }
+
+        let matches: Vec<&str> = COMMANDS.iter().map(|&(name, _)| name).filter(|name| name.starts_with(self.input.as_str())).collect();
+
+        match matches.len() {
+            0 => (),
+            1 => self.input = format!("{} ", matches[0]),
+            _ => {
+                let candidates = matches.join(" ");
+                self.print(candidates);
+            },
+        }
+    }
+
+    pub fn print(&mut self, line: String) {
+        warn!("Console: {}", line);
+        self.output.push_back(line);
+        if self.output.len() > MAX_OUTPUT {
+            self.output.pop_front();
+        }
+        self.dirty = true;
+    }
+
+    pub fn get_output(&self) -> &VecDeque<String> {
+        &self.output
+    }
+
+    pub fn get_overlay(&self) -> Option<String> {
+        if !self.is_open() {
+            return None;
+        }
+
+        match self.output.back() {
+            Some(last) => Some(format!("{} | > {}_", last, self.input)),
+            None => Some(format!("> {}_", self.input)),
+        }
+    }
+
+    pub fn take_pending(&mut self) -> Vec<String> {
+        self.pending.drain(..).collect()
+    }
+
+    pub fn request_reload(&mut self, window_id: WindowId) {
+        self.reload_request = Some(window_id);
+    }
+
+    pub fn take_reload_request(&mut self) -> Option<WindowId> {
+        self.reload_request.take()
+    }
+
+    pub fn take_dirty(&mut self) -> bool {
+        let dirty = self.dirty;
+        self.dirty = false;
+        dirty
+    }
+}
+
+#[derive(Debug)]
+pub struct TimeScale {
+    scale: Delta,
+}
+
+impl TimeScale {
+    pub fn new() -> TimeScale {
+        TimeScale {
+            scale: 1.0,
+        }
+    }
+
+    pub fn set_scale(&mut self, scale: Delta) {
+        self.scale = scale;
+    }
+
+    pub fn get_scale(&self) -> Delta {
+        self.scale
+    }
+}
+
+#[derive(Debug)]
+pub struct LayerVisibility {
+    hidden: HashSet<u8>,
+}
+
+impl LayerVisibility {
+    pub fn new() -> LayerVisibility {
+        LayerVisibility {
+            hidden: HashSet::new(),
+        }
+    }
+
+    pub fn set_visible(&mut self, layer: u8, visible: bool) {
+        if visible {
+            self.hidden.remove(&layer);
+        } else {
+            self.hidden.insert(layer);
+        }
+    }
+
+    pub fn is_visible(&self, layer: u8) -> bool {
+        !self.hidden.contains(&layer)
+    }
+}
+
+#[derive(Debug)]
+pub struct ConsoleSystem;
+
+impl ConsoleSystem {
+    pub fn new() -> ConsoleSystem {
+        ConsoleSystem
+    }
+}
+
+impl System<Step> for ConsoleSystem {
+    fn run(&mut self, arg: RunArg, step: Step) {
+        use specs::Join;
+
+        if let Step::Render(_) = step {
+            arg.fetch(|_| ());
+            return;
+        }
+
+        let (entities, mut cameras, mut render_ids, mut transforms, mut render_datas, mut console, mut time_scale, mut layer_visibility) = arg.fetch(|w|
+            (
+                w.entities(),
+                w.write::<Camera>(),
+                w.write::<RenderId>(),
+                w.write::<Transform>(),
+                w.write::<RenderData>(),
+                w.write_resource::<Console>(),
+                w.write_resource::<TimeScale>(),
+                w.write_resource::<LayerVisibility>()
+            )
+        );
+
+        let lines = console.take_pending();
+
+        if lines.is_empty() {
+            return;
+        }
+
+        let window_id = console.get_window_id().cloned();
+        let entities: Vec<_> = entities.collect();
+
+        for line in lines {
+            let args: Vec<&str> = line.split_whitespace().collect();
+
+            let result = match (args[0], &args[1..]) {
+                ("help", _) => Ok(COMMANDS.iter().map(|&(_, usage)| usage).collect::<Vec<&str>>().join(", ")),
+                ("spawn", rest) => parse_point(rest).and_then(|(x, y)| {
+                    let window_id = try!(window_id.clone().ok_or_else(|| "no window to spawn in".to_string()));
+
+                    let template = entities.iter().filter_map(|entity|
+                        match (render_ids.get(*entity), render_datas.get(*entity)) {
+                            (Some(render_id), Some(render_data)) if render_id.clone_window_id() == window_id => Some((render_id.clone(), render_data.clone())),
+                            _ => None,
+                        }
+                    ).next();
+
+                    let (render_id, render_data) = try!(template.ok_or_else(|| format!("nothing to spawn in {:?}", window_id)));
+
+                    let mut transform = Transform::new_identity();
+                    transform.set_pos(Vector3::new(x, y, 0.0));
+                    transform.snapshot();
+
+                    let entity = arg.create();
+                    render_ids.insert(entity, render_id);
+                    transforms.insert(entity, transform);
+                    render_datas.insert(entity, render_data);
+
+                    Ok(format!("spawned {:?} at {}, {}", entity, x, y))
+                }),
+                ("teleport", rest) => parse_point(rest).and_then(|(x, y)| {
+                    let window_id = try!(window_id.clone().ok_or_else(|| "no window to teleport in".to_string()));
+
+                    for camera in (&mut cameras).iter() {
+                        if camera.clone_window_id() == window_id {
+                            camera.set_offset(Point2::new(x, y));
+                        }
+                    }
+
+                    Ok(format!("teleported cameras in {:?} to {}, {}", window_id, x, y))
+                }),
+                ("timescale", rest) => parse_time_scale(rest).map(|scale| {
+                    time_scale.set_scale(scale);
+                    format!("time scale set to {}", scale)
+                }),
+                ("layer", rest) => match rest.first().and_then(|layer| layer.parse::<u8>().ok()) {
+                    Some(layer) => {
+                        let visible = match rest.get(1) {
+                            Some(&"on") => true,
+                            Some(&"off") => false,
+                            _ => !layer_visibility.is_visible(layer),
+                        };
+                        layer_visibility.set_visible(layer, visible);
+                        Ok(format!("layer {} {}", layer, if visible { "shown" } else { "hidden" }))
+                    },
+                    None => Err("usage: layer <layer> [on|off]".to_string()),
+                },
+                ("reload", _) => match window_id.clone() {
+                    Some(window_id) => {
+                        console.request_reload(window_id);
+                        Ok("reloading assets".to_string())
+                    },
+                    None => Err("no window to reload from".to_string()),
+                },
+                ("dump", _) => {
+                    let mut count = 0;
+
+                    for entity in &entities {
+                        let render_id = render_ids.get(*entity);
+                        let pos = transforms.get(*entity).map(|transform| transform.get_pos());
+                        let layer = render_datas.get(*entity).map(|render_data| render_data.get_layer());
+                        let camera = cameras.get(*entity).map(|camera| (camera.clone_window_id(), camera.get_offset()));
+
+                        warn!("{:?}: render_id: {:?}, pos: {:?}, layer: {:?}, camera: {:?}", entity, render_id, pos, layer, camera);
+                        count += 1;
+                    }
+
+                    Ok(format!("dumped {} entities to the log", count))
+                },
+                (name, _) => Err(format!("unknown command `{}`, try `help`", name)),
+            };
+
+            match result {
+                Ok(output) => console.print(output),
+                Err(output) => console.print(format!("error: {}", output)),
+            }
+        }
+    }
+}
+
+fn parse_point(args: &[&str]) -> Result<(f32, f32), String> {
+    match (args.get(0).and_then(|x| x.parse().ok()), args.get(1).and_then(|y| y.parse().ok())) {
+        (Some(x), Some(y)) => Ok((x, y)),
+        _ => Err("expected <x> <y>".to_string()),
+    }
+}
+
+// Control and console input only run on ticks, a scale of 0 would never tick again to undo it
+fn parse_time_scale(args: &[&str]) -> Result<Delta, String> {
+    match args.first().and_then(|scale| scale.parse::<Delta>().ok()) {
+        Some(scale) if scale.is_finite() && scale > 0.0 && scale <= MAX_TIME_SCALE => Ok(scale),
+        _ => Err(format!("usage: timescale <scale>, scale above 0 and up to {}", MAX_TIME_SCALE)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_point, parse_time_scale, MAX_TIME_SCALE};
+
+    #[test]
+    fn time_scale_accepts_sane_values() {
+        assert_eq!(parse_time_scale(&["0.01"]), Ok(0.01));
+        assert_eq!(parse_time_scale(&["0.5"]), Ok(0.5));
+        assert_eq!(parse_time_scale(&["2"]), Ok(2.0));
+        assert_eq!(parse_time_scale(&[MAX_TIME_SCALE.to_string().as_str()]), Ok(MAX_TIME_SCALE));
+    }
+
+    #[test]
+    fn time_scale_rejects_bad_values() {
+        for arg in &["0", "0.0", "-0", "inf", "-inf", "infinity", "nan", "NaN", "-1", "-0.5", "1e30", "fast", ""] {
+            assert!(parse_time_scale(&[*arg]).is_err(), "accepted {}", arg);
+        }
+        assert!(parse_time_scale(&[]).is_err());
+    }
+
+    #[test]
+    fn point_needs_two_numbers() {
+        assert_eq!(parse_point(&["1", "-2.5"]), Ok((1.0, -2.5)));
+        assert!(parse_point(&["1"]).is_err());
+        assert!(parse_point(&["1", "up"]).is_err());
+    }
+}
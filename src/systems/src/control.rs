@@ -1,44 +1,365 @@
+use std::collections::{HashMap, HashSet};
+
 use specs::{System, RunArg};
 use glutin::{MouseButton};
 
+use components::{Camera, RenderData, RenderId, Transform};
+use console::{Console, ConsoleKey};
 use event::{BackChannel, WindowedEvent};
-use utils::{Delta};
+use input::{Action, InputState};
+use math::{Point2};
+use replay::{InputLog};
+use utils::{GfxCoord, Step, WindowId};
+
+const CAMERA_PAN_SPEED: GfxCoord = 5.0;
 
 #[derive(Debug)]
 pub enum ToControl {
     MouseMoved(u32, u32),
     MouseInput(bool, MouseButton),
-    Right(bool),
-    Left(bool),
-    Up(bool),
-    Down(bool),
+    Action(Action, bool),
+    ActionRepeat(Action),
+    Focus(bool),
     Resize(u32, u32),
+    WindowOpened(Vec<(RenderId, RenderData)>, Vec<Camera>),
+    WindowClosed,
+    ConsoleToggle(bool),
+    ConsoleText(String),
+    ConsoleKey(ConsoleKey),
+    Quit,
 }
 
 #[derive(Debug)]
 pub enum FromControl {
-    Resize,
+    OpenWindow(String, u32, u32, [f32; 4]),
+    CloseWindow,
+    ConsoleOverlay(Option<String>),
+    ReloadAssets,
 }
 
 pub type WindowedToControl = WindowedEvent<ToControl>;
 
 pub type WindowedFromControl = WindowedEvent<FromControl>;
 
+#[derive(Debug)]
+pub struct QuitFlag {
+    quit: bool,
+}
+
+impl QuitFlag {
+    pub fn new() -> QuitFlag {
+        QuitFlag {
+            quit: false,
+        }
+    }
+
+    pub fn set(&mut self) {
+        self.quit = true;
+    }
+
+    pub fn is_set(&self) -> bool {
+        self.quit
+    }
+}
+
+#[derive(Debug)]
+pub enum WindowRequest {
+    Open(WindowId, String, u32, u32, [f32; 4]),
+    Close(WindowId),
+}
+
+#[derive(Debug)]
+pub struct WindowRequests {
+    requests: Vec<WindowRequest>,
+}
+
+impl WindowRequests {
+    pub fn new() -> WindowRequests {
+        WindowRequests {
+            requests: vec!(),
+        }
+    }
+
+    pub fn open(&mut self, parent_id: WindowId, title: String, width: u32, height: u32, clear_color: [f32; 4]) {
+        self.requests.push(WindowRequest::Open(parent_id, title, width, height, clear_color));
+    }
+
+    pub fn close(&mut self, window_id: WindowId) {
+        self.requests.push(WindowRequest::Close(window_id));
+    }
+
+    pub fn take(&mut self) -> Vec<WindowRequest> {
+        self.requests.drain(..).collect()
+    }
+}
+
 #[derive(Debug)]
 pub struct ControlSystem {
     back_channel: BackChannel<WindowedToControl, WindowedFromControl>,
+    input_log: InputLog,
+    active_windows: HashSet<WindowId>,
+    window_sizes: HashMap<WindowId, (u32, u32)>,
 }
 
 impl ControlSystem {
-    pub fn new(back_channel: BackChannel<WindowedToControl, WindowedFromControl>) -> ControlSystem {
+    pub fn new(back_channel: BackChannel<WindowedToControl, WindowedFromControl>, input_log: InputLog) -> ControlSystem {
         ControlSystem {
             back_channel: back_channel,
+            input_log: input_log,
+            active_windows: HashSet::new(),
+            window_sizes: HashMap::new(),
         }
     }
 }
 
-impl System<Delta> for ControlSystem {
-    fn run(&mut self, arg: RunArg, _: Delta) {
-        arg.fetch(|_| ());
+impl System<Step> for ControlSystem {
+    fn run(&mut self, arg: RunArg, step: Step) {
+        use specs::Join;
+
+        let (tick, delta) = match step {
+            Step::Tick(tick, delta) => (tick, delta),
+            Step::Render(_) => {
+                arg.fetch(|_| ());
+                return;
+            },
+        };
+
+        let mut quit = false;
+        let mut events = vec!();
+        let mut closed_windows = vec!();
+
+        loop {
+            match self.back_channel.try_recv_to() {
+                Ok(Some(event)) => events.push(event),
+                Ok(None) => break,
+                Err(err) => {
+                    warn!("Control System channel closed: {}", err);
+                    quit = true;
+                    break;
+                },
+            }
+        }
+
+        let events = self.input_log.process(tick, events);
+
+        let (entities, mut cameras, mut render_ids, mut transforms, mut render_datas, mut quit_flag, mut window_requests, mut input_state, mut console) = arg.fetch(|w|
+            (
+                w.entities(),
+                w.write::<Camera>(),
+                w.write::<RenderId>(),
+                w.write::<Transform>(),
+                w.write::<RenderData>(),
+                w.write_resource::<QuitFlag>(),
+                w.write_resource::<WindowRequests>(),
+                w.write_resource::<InputState>(),
+                w.write_resource::<Console>()
+            )
+        );
+
+        input_state.begin_tick();
+
+        let mut closed_overlays = vec!();
+
+        for (window_id, event) in events {
+            match event {
+                ToControl::Quit => quit = true,
+                ToControl::Resize(width, height) => {
+                    if width == 0 || height == 0 {
+                        continue;
+                    }
+
+                    warn!("Resizing {:?} to {}x{}", window_id, width, height);
+                    self.window_sizes.insert(window_id.clone(), (width, height));
+
+                    for camera in (&mut cameras).iter() {
+                        if camera.clone_window_id() == window_id {
+                            camera.resize(width, height);
+                        }
+                    }
+                },
+                ToControl::WindowOpened(renders, new_cameras) => {
+                    warn!("Spawning {} renders for {:?}", renders.len(), window_id);
+
+                    for camera in new_cameras {
+                        let entity = arg.create();
+                        cameras.insert(entity, camera);
+                    }
+
+                    for (render_id, render_data) in renders {
+                        let entity = arg.create();
+                        render_ids.insert(entity, render_id);
+                        transforms.insert(entity, Transform::new_identity());
+                        render_datas.insert(entity, render_data);
+                    }
+                },
+                ToControl::WindowClosed => {
+                    warn!("Deleting renders for {:?}", window_id);
+                    self.window_sizes.remove(&window_id);
+                    input_state.remove_window(&window_id);
+                    self.active_windows.remove(&window_id);
+                    if console.get_window_id() == Some(&window_id) {
+                        console.close();
+                    }
+                    closed_windows.push(window_id);
+                },
+                ToControl::MouseMoved(x, y) => {
+                    input_state.set_mouse_screen(window_id, x, y);
+                },
+                ToControl::MouseInput(pressed, button) => {
+                    if pressed {
+                        input_state.click(window_id.clone(), button);
+                    }
+                    debug!("Mouse {:?} {} at {:?} in {:?}", button, if pressed { "pressed" } else { "released" }, input_state.get_mouse_world(&window_id), window_id);
+                },
+                ToControl::Action(action, pressed) => {
+                    if pressed {
+                        input_state.press(action);
+                    } else {
+                        input_state.release(action);
+                    }
+
+                    if action == Action::Quit && pressed {
+                        quit = true;
+                    }
+
+                    if pressed {
+                        self.active_windows.insert(window_id);
+                    }
+                },
+                ToControl::ActionRepeat(action) => {
+                    input_state.repeat(action);
+                },
+                ToControl::Focus(focused) => {
+                    if focused {
+                        input_state.set_focused(Some(window_id));
+                    } else {
+                        if input_state.is_focused(&window_id) {
+                            input_state.set_focused(None);
+                        }
+                        input_state.release_all();
+                        self.active_windows.clear();
+                    }
+                },
+                ToControl::ConsoleToggle(open) => {
+                    if let Some(old_window) = console.get_window_id().cloned() {
+                        closed_overlays.push(old_window);
+                    }
+
+                    if open {
+                        console.open(window_id);
+                    } else {
+                        console.close();
+                    }
+                },
+                ToControl::ConsoleText(text) => {
+                    console.insert_text(&text);
+                },
+                ToControl::ConsoleKey(key) => {
+                    console.key(key);
+                },
+            }
+        }
+
+        if !self.active_windows.is_empty() {
+            let pan_x = (input_state.is_held(Action::MoveRight) as u8 as GfxCoord - input_state.is_held(Action::MoveLeft) as u8 as GfxCoord) * CAMERA_PAN_SPEED * delta as GfxCoord;
+            let pan_y = (input_state.is_held(Action::MoveUp) as u8 as GfxCoord - input_state.is_held(Action::MoveDown) as u8 as GfxCoord) * CAMERA_PAN_SPEED * delta as GfxCoord;
+
+            if pan_x != 0.0 || pan_y != 0.0 {
+                for camera in (&mut cameras).iter() {
+                    if self.active_windows.contains(&camera.clone_window_id()) {
+                        let offset = camera.get_offset();
+                        camera.set_offset(Point2::new(offset.x + pan_x, offset.y + pan_y));
+                    }
+                }
+            }
+        }
+
+        for window_id in input_state.get_mouse_windows() {
+            let (x, y) = match input_state.get_mouse_screen(&window_id) {
+                Some(screen) => screen,
+                None => continue,
+            };
+
+            let (width, height) = match self.window_sizes.get(&window_id) {
+                Some(&size) => size,
+                None => continue,
+            };
+
+            let screen_x = x as GfxCoord / width as GfxCoord;
+            let screen_y = y as GfxCoord / height as GfxCoord;
+
+            let mut world = None;
+            let mut top_order = None;
+
+            for camera in (&cameras).iter() {
+                if camera.clone_window_id() != window_id {
+                    continue;
+                }
+
+                let screen_point = Point2::new(screen_x, screen_y);
+
+                if !camera.get_viewport().contains_screen_point(screen_point) {
+                    continue;
+                }
+
+                if top_order.map_or(true, |order| camera.get_order() >= order) {
+                    top_order = Some(camera.get_order());
+                    world = Some(camera.screen_to_world_point(screen_point));
+                }
+            }
+
+            input_state.set_mouse_world(&window_id, world);
+        }
+
+        if !closed_windows.is_empty() {
+            for entity in entities {
+                let closed = match (render_ids.get(entity), cameras.get(entity)) {
+                    (Some(render_id), _) => closed_windows.contains(&render_id.clone_window_id()),
+                    (_, Some(camera)) => closed_windows.contains(&camera.clone_window_id()),
+                    _ => false,
+                };
+
+                if closed {
+                    arg.delete(entity);
+                }
+            }
+        }
+
+        for request in window_requests.take() {
+            let result = match request {
+                WindowRequest::Open(parent_id, title, width, height, clear_color) => self.back_channel.send_from((parent_id, FromControl::OpenWindow(title, width, height, clear_color))),
+                WindowRequest::Close(window_id) => self.back_channel.send_from((window_id, FromControl::CloseWindow)),
+            };
+
+            if let Err(err) = result {
+                warn!("Control System unable to send window request: {}", err);
+            }
+        }
+
+        let console_window = console.get_window_id().cloned();
+        let mut overlays: Vec<_> = closed_overlays.into_iter().filter(|window_id| Some(window_id) != console_window.as_ref()).map(|window_id| (window_id, None)).collect();
+
+        if console.take_dirty() {
+            if let Some(window_id) = console_window {
+                overlays.push((window_id, console.get_overlay()));
+            }
+        }
+
+        for (window_id, overlay) in overlays {
+            if let Err(err) = self.back_channel.send_from((window_id, FromControl::ConsoleOverlay(overlay))) {
+                warn!("Control System unable to send console overlay: {}", err);
+            }
+        }
+
+        if let Some(window_id) = console.take_reload_request() {
+            if let Err(err) = self.back_channel.send_from((window_id, FromControl::ReloadAssets)) {
+                warn!("Control System unable to send reload request: {}", err);
+            }
+        }
+
+        if quit {
+            warn!("Control System received Quit");
+            quit_flag.set();
+        }
     }
 }
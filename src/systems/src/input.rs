@@ -0,0 +1,355 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use glutin::{MouseButton};
+use math::{Point2};
+use utils::{GfxCoord, WindowId};
+
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
+pub enum Action {
+    MoveRight,
+    MoveLeft,
+    MoveUp,
+    MoveDown,
+    Wait,
+    PickUp,
+    OpenInventory,
+    ReloadBindings,
+    ToggleConsole,
+    Quit,
+}
+
+pub const ACTIONS: &'static [Action] = &[
+    Action::MoveRight,
+    Action::MoveLeft,
+    Action::MoveUp,
+    Action::MoveDown,
+    Action::Wait,
+    Action::PickUp,
+    Action::OpenInventory,
+    Action::ReloadBindings,
+    Action::ToggleConsole,
+    Action::Quit,
+];
+
+impl Action {
+    pub fn get_name(&self) -> &'static str {
+        match *self {
+            Action::MoveRight => "move_right",
+            Action::MoveLeft => "move_left",
+            Action::MoveUp => "move_up",
+            Action::MoveDown => "move_down",
+            Action::Wait => "wait",
+            Action::PickUp => "pick_up",
+            Action::OpenInventory => "open_inventory",
+            Action::ReloadBindings => "reload_bindings",
+            Action::ToggleConsole => "toggle_console",
+            Action::Quit => "quit",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Action> {
+        ACTIONS.iter().find(|action| action.get_name() == name).cloned()
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.get_name())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MousePosition {
+    screen: (u32, u32),
+    world: Option<Point2<GfxCoord>>,
+}
+
+impl MousePosition {
+    pub fn get_screen(&self) -> (u32, u32) {
+        self.screen
+    }
+
+    pub fn get_world(&self) -> Option<Point2<GfxCoord>> {
+        self.world
+    }
+}
+
+#[derive(Debug)]
+pub struct InputState {
+    held: HashSet<Action>,
+    just_pressed: HashSet<Action>,
+    just_released: HashSet<Action>,
+    repeated: HashSet<Action>,
+    clicks: Vec<(WindowId, MouseButton)>,
+    mouse_positions: HashMap<WindowId, MousePosition>,
+    focused: Option<WindowId>,
+}
+
+impl InputState {
+    pub fn new() -> InputState {
+        InputState {
+            held: HashSet::new(),
+            just_pressed: HashSet::new(),
+            just_released: HashSet::new(),
+            repeated: HashSet::new(),
+            clicks: vec!(),
+            mouse_positions: HashMap::new(),
+            focused: None,
+        }
+    }
+
+    pub fn begin_tick(&mut self) {
+        self.just_pressed.clear();
+        self.just_released.clear();
+        self.repeated.clear();
+        self.clicks.clear();
+    }
+
+    pub fn press(&mut self, action: Action) {
+        if self.held.insert(action) {
+            self.just_pressed.insert(action);
+        }
+    }
+
+    pub fn release(&mut self, action: Action) {
+        if self.held.remove(&action) {
+            self.just_released.insert(action);
+        }
+    }
+
+    pub fn release_all(&mut self) {
+        for action in self.held.drain() {
+            self.just_released.insert(action);
+        }
+    }
+
+    pub fn repeat(&mut self, action: Action) {
+        if self.held.contains(&action) {
+            self.repeated.insert(action);
+        }
+    }
+
+    pub fn is_held(&self, action: Action) -> bool {
+        self.held.contains(&action)
+    }
+
+    pub fn was_pressed(&self, action: Action) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    pub fn was_released(&self, action: Action) -> bool {
+        self.just_released.contains(&action)
+    }
+
+    pub fn was_repeated(&self, action: Action) -> bool {
+        self.repeated.contains(&action)
+    }
+
+    pub fn was_pressed_or_repeated(&self, action: Action) -> bool {
+        self.was_pressed(action) || self.was_repeated(action)
+    }
+
+    pub fn click(&mut self, window_id: WindowId, button: MouseButton) {
+        self.clicks.push((window_id, button));
+    }
+
+    pub fn get_clicks(&self) -> &[(WindowId, MouseButton)] {
+        self.clicks.as_slice()
+    }
+
+    pub fn set_mouse_screen(&mut self, window_id: WindowId, x: u32, y: u32) {
+        let position = self.mouse_positions.entry(window_id).or_insert(MousePosition {
+            screen: (x, y),
+            world: None,
+        });
+        position.screen = (x, y);
+    }
+
+    pub fn set_mouse_world(&mut self, window_id: &WindowId, world: Option<Point2<GfxCoord>>) {
+        if let Some(position) = self.mouse_positions.get_mut(window_id) {
+            position.world = world;
+        }
+    }
+
+    pub fn get_mouse(&self, window_id: &WindowId) -> Option<&MousePosition> {
+        self.mouse_positions.get(window_id)
+    }
+
+    pub fn get_mouse_screen(&self, window_id: &WindowId) -> Option<(u32, u32)> {
+        self.get_mouse(window_id).map(|position| position.get_screen())
+    }
+
+    pub fn get_mouse_world(&self, window_id: &WindowId) -> Option<Point2<GfxCoord>> {
+        self.get_mouse(window_id).and_then(|position| position.get_world())
+    }
+
+    pub fn get_mouse_windows(&self) -> Vec<WindowId> {
+        let mut window_ids: Vec<WindowId> = self.mouse_positions.keys().cloned().collect();
+        window_ids.sort();
+        window_ids
+    }
+
+    pub fn set_focused(&mut self, focused: Option<WindowId>) {
+        self.focused = focused;
+    }
+
+    pub fn get_focused(&self) -> Option<&WindowId> {
+        self.focused.as_ref()
+    }
+
+    pub fn is_focused(&self, window_id: &WindowId) -> bool {
+        self.focused.as_ref() == Some(window_id)
+    }
+
+    pub fn remove_window(&mut self, window_id: &WindowId) {
+        self.mouse_positions.remove(window_id);
+        if self.is_focused(window_id) {
+            self.focused = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glutin::{MouseButton};
+    use math::{Point2};
+    use utils::{WindowId};
+
+    use super::{Action, InputState, ACTIONS};
+
+    #[test]
+    fn action_names_round_trip() {
+        for action in ACTIONS {
+            assert_eq!(Action::from_name(action.get_name()), Some(*action));
+        }
+        assert_eq!(Action::from_name("fly"), None);
+    }
+
+    #[test]
+    fn press_is_just_pressed_for_one_tick() {
+        let mut input = InputState::new();
+
+        input.begin_tick();
+        input.press(Action::MoveUp);
+        assert!(input.is_held(Action::MoveUp));
+        assert!(input.was_pressed(Action::MoveUp));
+
+        input.begin_tick();
+        assert!(input.is_held(Action::MoveUp));
+        assert!(!input.was_pressed(Action::MoveUp));
+    }
+
+    #[test]
+    fn second_press_while_held_is_not_a_new_press() {
+        let mut input = InputState::new();
+
+        input.press(Action::Wait);
+        input.begin_tick();
+        input.press(Action::Wait);
+
+        assert!(!input.was_pressed(Action::Wait));
+    }
+
+    #[test]
+    fn release_only_counts_when_held() {
+        let mut input = InputState::new();
+
+        input.release(Action::Wait);
+        assert!(!input.was_released(Action::Wait));
+
+        input.press(Action::Wait);
+        input.begin_tick();
+        input.release(Action::Wait);
+        assert!(!input.is_held(Action::Wait));
+        assert!(input.was_released(Action::Wait));
+    }
+
+    #[test]
+    fn press_and_release_in_one_tick_is_seen() {
+        let mut input = InputState::new();
+
+        input.begin_tick();
+        input.press(Action::PickUp);
+        input.release(Action::PickUp);
+
+        assert!(input.was_pressed(Action::PickUp));
+        assert!(input.was_released(Action::PickUp));
+        assert!(!input.is_held(Action::PickUp));
+    }
+
+    #[test]
+    fn repeat_needs_a_held_action() {
+        let mut input = InputState::new();
+
+        input.repeat(Action::MoveLeft);
+        assert!(!input.was_repeated(Action::MoveLeft));
+
+        input.press(Action::MoveLeft);
+        input.begin_tick();
+        input.repeat(Action::MoveLeft);
+        assert!(input.was_repeated(Action::MoveLeft));
+        assert!(input.was_pressed_or_repeated(Action::MoveLeft));
+
+        input.begin_tick();
+        assert!(!input.was_pressed_or_repeated(Action::MoveLeft));
+    }
+
+    #[test]
+    fn release_all_releases_every_held_action() {
+        let mut input = InputState::new();
+
+        input.press(Action::MoveUp);
+        input.press(Action::MoveRight);
+        input.begin_tick();
+        input.release_all();
+
+        assert!(!input.is_held(Action::MoveUp));
+        assert!(!input.is_held(Action::MoveRight));
+        assert!(input.was_released(Action::MoveUp));
+        assert!(input.was_released(Action::MoveRight));
+    }
+
+    #[test]
+    fn clicks_last_one_tick() {
+        let mut input = InputState::new();
+
+        input.click(WindowId(1), MouseButton::Left);
+        assert_eq!(input.get_clicks().len(), 1);
+
+        input.begin_tick();
+        assert!(input.get_clicks().is_empty());
+    }
+
+    #[test]
+    fn mouse_is_tracked_per_window() {
+        let mut input = InputState::new();
+
+        input.set_mouse_world(&WindowId(1), Some(Point2::new(1.0, 1.0)));
+        assert_eq!(input.get_mouse_world(&WindowId(1)), None);
+
+        input.set_mouse_screen(WindowId(1), 10, 20);
+        input.set_mouse_screen(WindowId(2), 30, 40);
+        input.set_mouse_world(&WindowId(1), Some(Point2::new(1.0, 2.0)));
+
+        assert_eq!(input.get_mouse_screen(&WindowId(1)), Some((10, 20)));
+        assert_eq!(input.get_mouse_screen(&WindowId(2)), Some((30, 40)));
+        assert_eq!(input.get_mouse_world(&WindowId(1)), Some(Point2::new(1.0, 2.0)));
+        assert_eq!(input.get_mouse_world(&WindowId(2)), None);
+
+        input.remove_window(&WindowId(1));
+        assert_eq!(input.get_mouse_screen(&WindowId(1)), None);
+    }
+
+    #[test]
+    fn removing_the_focused_window_clears_focus() {
+        let mut input = InputState::new();
+
+        input.set_focused(Some(WindowId(2)));
+        input.remove_window(&WindowId(1));
+        assert!(input.is_focused(&WindowId(2)));
+
+        input.remove_window(&WindowId(2));
+        assert_eq!(input.get_focused(), None);
+    }
+}
@@ -8,7 +8,14 @@ extern crate graphics;
 extern crate math;
 extern crate utils;
 
-pub use dependencies::{specs, glutin};
+pub use dependencies::{specs, glutin, rand};
 
+pub mod console;
 pub mod control;
+pub mod input;
+pub mod movement;
+pub mod null_render;
+pub mod picking;
 pub mod render;
+pub mod replay;
+pub mod rng;
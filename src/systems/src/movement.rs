@@ -0,0 +1,41 @@
+use math::{Vector3};
+use specs::{System, RunArg};
+
+use components::{RenderId, Transform};
+use utils::{Step, GfxCoord};
+
+const DRIFT_SPEED: GfxCoord = 0.6;
+
+#[derive(Debug)]
+pub struct MovementSystem;
+
+impl MovementSystem {
+    pub fn new() -> MovementSystem {
+        MovementSystem
+    }
+}
+
+impl System<Step> for MovementSystem {
+    fn run(&mut self, arg: RunArg, step: Step) {
+        use specs::Join;
+
+        let delta = match step {
+            Step::Tick(_, delta) => delta,
+            Step::Render(_) => {
+                arg.fetch(|_| ());
+                return;
+            },
+        };
+
+        let (render_ids, mut transforms) = arg.fetch(|w|
+            (
+                w.read::<RenderId>(),
+                w.write::<Transform>()
+            )
+        );
+
+        for (_, transform) in (&render_ids, &mut transforms).iter() {
+            transform.add_pos(Vector3::new(DRIFT_SPEED * delta as GfxCoord, 0.0, 0.0));
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use specs::{System, RunArg};
+
+use event::{BackChannel};
+use render::{WindowedToRender, WindowedFromRender, ToRender, FromRender};
+use utils::{Step};
+
+#[derive(Debug)]
+pub struct NullRenderSystem {
+    back_channel: BackChannel<WindowedToRender, WindowedFromRender>,
+    quitting: bool,
+}
+
+impl NullRenderSystem {
+    pub fn new(back_channel: BackChannel<WindowedToRender, WindowedFromRender>) -> NullRenderSystem {
+        NullRenderSystem {
+            back_channel: back_channel,
+            quitting: false,
+        }
+    }
+
+    fn process_event(&mut self, event: WindowedToRender) {
+        match event {
+            (window_id, ToRender::Encoder(encoder)) => {
+                if let Err(err) = self.back_channel.send_from((window_id, FromRender::Encoder(encoder))) {
+                    warn!("Null Render System unable to return encoder: {}", err);
+                }
+            },
+            (_, ToRender::GraphicsData(..)) | (_, ToRender::OpenWindow(..)) | (_, ToRender::ReplaceBundles(..)) | (_, ToRender::CloseWindow) => (),
+            (_, ToRender::Quit) => {
+                warn!("Null Render System received Quit");
+                self.quitting = true;
+            },
+        }
+    }
+}
+
+impl System<Step> for NullRenderSystem {
+    fn run(&mut self, arg: RunArg, _: Step) {
+        while !self.quitting {
+            match self.back_channel.try_recv_to() {
+                Ok(Some(event)) => self.process_event(event),
+                Ok(None) => break,
+                Err(err) => {
+                    warn!("Null Render System channel closed: {}", err);
+                    self.quitting = true;
+                },
+            }
+        }
+
+        arg.fetch(|_| ());
+    }
+}
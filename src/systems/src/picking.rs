@@ -0,0 +1,146 @@
+use std::collections::{BTreeMap, HashMap};
+
+use specs::{System, RunArg, Entity};
+use glutin::{MouseButton};
+
+use components::{RenderData, RenderId, Transform};
+use event::{Publisher};
+use input::{InputState};
+use utils::{Step, WindowId};
+
+#[derive(Debug, Clone)]
+pub enum PickEvent {
+    HoverStart(WindowId, Entity),
+    HoverEnd(WindowId, Entity),
+    Click(WindowId, Entity, MouseButton),
+}
+
+#[derive(Debug)]
+pub struct Picking {
+    // Ordered by window so hover events come out the same way every tick
+    hovered: BTreeMap<WindowId, Vec<Entity>>,
+    events: Vec<PickEvent>,
+}
+
+impl Picking {
+    pub fn new() -> Picking {
+        Picking {
+            hovered: BTreeMap::new(),
+            events: vec!(),
+        }
+    }
+
+    pub fn get_hovered(&self, window_id: &WindowId) -> &[Entity] {
+        self.hovered.get(window_id).map(|hovered| hovered.as_slice()).unwrap_or(&[])
+    }
+
+    pub fn get_topmost(&self, window_id: &WindowId) -> Option<Entity> {
+        self.get_hovered(window_id).first().cloned()
+    }
+
+    pub fn get_events(&self) -> &[PickEvent] {
+        self.events.as_slice()
+    }
+}
+
+#[derive(Debug)]
+pub struct PickingSystem {
+    publisher: Publisher,
+}
+
+impl PickingSystem {
+    pub fn new(publisher: Publisher) -> PickingSystem {
+        PickingSystem {
+            publisher: publisher,
+        }
+    }
+}
+
+impl System<Step> for PickingSystem {
+    fn run(&mut self, arg: RunArg, step: Step) {
+        if let Step::Render(_) = step {
+            arg.fetch(|_| ());
+            return;
+        }
+
+        let (entities, render_ids, transforms, render_datas, input_state, mut picking) = arg.fetch(|w|
+            (
+                w.entities(),
+                w.read::<RenderId>(),
+                w.read::<Transform>(),
+                w.read::<RenderData>(),
+                w.read_resource::<InputState>(),
+                w.write_resource::<Picking>()
+            )
+        );
+
+        let points: HashMap<WindowId, _> = input_state.get_mouse_windows().into_iter().filter_map(|window_id|
+            input_state.get_mouse_world(&window_id).map(|point| (window_id, point))
+        ).collect();
+
+        let mut hits: HashMap<WindowId, Vec<(u8, Entity)>> = HashMap::new();
+
+        if !points.is_empty() {
+            for entity in entities {
+                let (render_id, transform, render_data) = match (render_ids.get(entity), transforms.get(entity), render_datas.get(entity)) {
+                    (Some(render_id), Some(transform), Some(render_data)) => (render_id, transform, render_data),
+                    _ => continue,
+                };
+
+                let window_id = render_id.clone_window_id();
+
+                let hit = match points.get(&window_id) {
+                    Some(point) => transform.contains_point(*point),
+                    None => false,
+                };
+
+                if hit {
+                    hits.entry(window_id).or_insert_with(|| vec!()).push((render_data.get_layer(), entity));
+                }
+            }
+        }
+
+        let mut hovered = BTreeMap::new();
+
+        for (window_id, mut window_hits) in hits {
+            window_hits.sort_by(|a, b| b.0.cmp(&a.0));
+            hovered.insert(window_id, window_hits.into_iter().map(|(_, entity)| entity).collect::<Vec<Entity>>());
+        }
+
+        let mut events = vec!();
+
+        for (window_id, old) in &picking.hovered {
+            let new = hovered.get(window_id);
+            for entity in old {
+                if new.map_or(true, |new| !new.contains(entity)) {
+                    events.push(PickEvent::HoverEnd(window_id.clone(), *entity));
+                }
+            }
+        }
+
+        for (window_id, new) in &hovered {
+            let old = picking.hovered.get(window_id);
+            for entity in new {
+                if old.map_or(true, |old| !old.contains(entity)) {
+                    events.push(PickEvent::HoverStart(window_id.clone(), *entity));
+                }
+            }
+        }
+
+        for &(ref window_id, button) in input_state.get_clicks() {
+            if let Some(entity) = hovered.get(window_id).and_then(|hovered| hovered.first()) {
+                events.push(PickEvent::Click(window_id.clone(), *entity, button));
+            }
+        }
+
+        for event in &events {
+            let window_id = match *event {
+                PickEvent::HoverStart(ref window_id, _) | PickEvent::HoverEnd(ref window_id, _) | PickEvent::Click(ref window_id, _, _) => window_id.clone(),
+            };
+            self.publisher.publish_window(window_id, event.clone());
+        }
+
+        picking.hovered = hovered;
+        picking.events = events;
+    }
+}
@@ -1,16 +1,21 @@
 use std::collections::{HashMap};
 
-use math::{Vector3};
 use specs::{System, RunArg};
 
-use components::{RenderId, Transform, Camera, RenderData};
+use components::{RenderId, Transform, Camera, RenderData, Viewport, Material, BlendMode};
+use console::{LayerVisibility};
 use event::{BackChannel, WindowedEvent};
-use graphics::{OutColor, OutDepth, Encoder, Bundle, Shaders, make_shaders, ProjectionData, TextureData, GlFactory, Packet, RlTexture, Primitive, pipe, FilterMethod, WrapMode, SamplerInfo, FactoryExt, Factory};
-use utils::{Delta, WindowId};
+use graphics::{OutColor, OutDepth, Encoder, Bundle, GraphicsError, Shaders, Materials, MaterialData, ProjectionData, RenderState, TextureData, GlFactory, Packet, RlTexture, Primitive, pipe, ScissorRect, FactoryExt, Factory, PipelineCache, TextureSettings};
+use graphics::{make_pipe_init, make_batch_pipe_init, SpriteBatch, BatchVertex, Bind, BufferRole, IntoIndexBuffer, Slice, batch_pipe, make_batch_indices, DEFAULT_BATCH_CAPACITY};
+use utils::{Step, GfxCoord, WindowId};
 
 pub enum ToRender {
     GraphicsData(OutColor, OutDepth),
     Encoder(Encoder),
+    OpenWindow(OutColor, OutDepth, [f32; 4], Vec<Bundle>, Vec<SpriteBatch>),
+    ReplaceBundles(Vec<Bundle>, Vec<SpriteBatch>),
+    CloseWindow,
+    Quit,
 }
 
 pub enum FromRender {
@@ -24,6 +29,8 @@ pub type WindowedFromRender = WindowedEvent<FromRender>;
 pub struct RenderSystem {
     back_channel: BackChannel<WindowedToRender, WindowedFromRender>,
     sys: RenderSystemSend,
+    time: GfxCoord,
+    quitting: bool,
 }
 
 impl RenderSystem {
@@ -31,215 +38,455 @@ impl RenderSystem {
         RenderSystem {
             back_channel: back_channel,
             sys: send,
+            time: 0.0,
+            quitting: false,
         }
     }
 
-    fn render(&mut self, arg: &RunArg, window_id: WindowId, mut encoder: Encoder) {
+    fn render(&mut self, arg: &RunArg, window_id: WindowId, mut encoder: Encoder, alpha: GfxCoord) {
         use specs::Join;
 
         // warn!("Starting Render");
-        let (render_ids, mut transforms, mut cameras, mut render_datas) = arg.fetch(|w|
+        let (render_ids, transforms, mut cameras, render_datas, layer_visibility) = arg.fetch(|w|
             (
                 w.read::<RenderId>(),
-                w.write::<Transform>(),
+                w.read::<Transform>(),
                 w.write::<Camera>(),
-                w.write::<RenderData>()
+                w.read::<RenderData>(),
+                w.read_resource::<LayerVisibility>()
             )
         );
 
-        let out = self.sys.outs.get(&window_id).unwrap_or_else(|| panic!("Unable to find Outs for: {:?}", window_id));
-
-        if window_id.0 == 1 {
-            encoder.clear(&out.0, [1.0, 0.0, 0.0, 1.0]);
-        } else {
-            encoder.clear(&out.0, [0.0, 0.0, 1.0, 1.0]);
-            self.back_channel.send_from((window_id, FromRender::Encoder(encoder)));
-            return;
-        }
+        let out = match self.sys.outs.get(&window_id).cloned() {
+            Some(out) => out,
+            None => {
+                warn!("Dropping encoder for closed window: {:?}", window_id);
+                return;
+            },
+        };
+        let clear_color = self.sys.clear_colors.get(&window_id).cloned().unwrap_or([0.0, 0.0, 0.0, 1.0]);
 
+        encoder.clear(&out.0, clear_color);
         encoder.clear_depth(&out.1, 1.0);
 
-        let (view, proj, dirty_cam) = {
-            let mut camera = {
-                let mut camera_opt = None;
+        let (width, height, _, _) = out.0.get_dimensions();
 
-                for c in (&mut cameras).iter() {
-                    if c.is_main() {
-                        camera_opt = Some(c);
-                    }
-                }
+        let mut views = vec!();
 
-                camera_opt.expect("No camera entity was found by render")
-            };
+        for camera in (&mut cameras).iter() {
+            if camera.clone_window_id() != window_id {
+                continue;
+            }
 
-            (camera.get_view(), camera.get_proj(), camera.take_dirty())
-        };
+            let proj = camera.get_viewport_proj();
+            let scissor = viewport_scissor(camera.get_viewport(), width, height);
+
+            views.push((camera.get_order(), camera.get_view(), proj, scissor, camera.take_dirty()));
+        }
+
+        if views.is_empty() {
+            warn!("No camera found for {:?}, sending a blank frame", window_id);
+            send_encoder(&mut self.back_channel, window_id, encoder);
+            return;
+        }
+
+        views.sort_by_key(|k| k.0);
 
         let mut datas = vec!();
 
-        for (render_id, transform, mut render_data) in (&render_ids, &mut transforms, &mut render_datas).iter() {
+        for (render_id, transform, render_data) in (&render_ids, &transforms, &render_datas).iter() {
             // warn!("{:?}, {:?}", window_id, render_id.clone_window_id());
             if render_id.clone_window_id() != window_id {
                 // warn!("Render Ids: {:?}, {:?}", render_id.clone_window_id(), &window_id);
                 continue;
             }
 
-            transform.add_pos(Vector3::new(0.01, 0.0, 0.0));
+            if !layer_visibility.is_visible(render_data.get_layer()) {
+                continue;
+            }
 
-            let mut projection_data = None;
+            let material = render_data.get_material();
+            let render_state = RenderState::new(render_data.get_blend_mode(), render_data.get_depth_write());
 
-            if true {//dirty_cam || transform.take_dirty() {
-                projection_data = Some(
-                    ProjectionData {
-                        model: transform.get_model().into(),
-                        view: view.into(),
-                        proj: proj.into(),
-                    }
-                );
-            }
+            // Batches share one draw per texture, so only plain textured alpha sprites can join them
+            let batched = render_data.is_batched()
+                && material == Material::Textured
+                && !render_data.has_flash()
+                && render_state == RenderState::new_default();
 
-            let mut texture_data = None;
+            let mut material_data = None;
 
-            if render_data.take_dirty() {
-                texture_data = Some(
-                    TextureData {
-                        tint: render_data.get_tint(),
-                        spritesheet_rect: render_data.get_spritesheet_rect(),
-                        spritesheet_size: render_data.get_spritesheet_size(),
-                        mirror_x: render_data.get_mirror_x(),
-                        mirror_y: render_data.get_mirror_y(),
-                    }
-                );
+            if !batched {
+                material_data = Some(MaterialData::new(render_data.get_material_params(), render_data.get_palette(), self.time));
             }
 
-            datas.push((render_id.get_render_id_num(), render_data.get_layer(), texture_data, projection_data));
+            // Entities can share a bundle, so its texture data is uploaded again on every draw
+            let texture_data = TextureData {
+                tint: render_data.get_tint(),
+                spritesheet_rect: render_data.get_spritesheet_rect(),
+                spritesheet_size: render_data.get_spritesheet_size(),
+                mirror_x: render_data.get_mirror_x(),
+                mirror_y: render_data.get_mirror_y(),
+            };
+
+            datas.push((render_id.get_render_id_num(), render_data.get_layer(), batched, texture_data, transform.get_interpolated_model(alpha), material, material_data, render_state));
         }
 
-        datas.sort_by_key(|k| k.1);
+        datas.sort_by_key(|k| (k.1, k.7.get_blend_mode().get_order()));
 
-        for data in datas {
-            let b = &self.sys.bundles_map.get(&window_id).unwrap_or_else(|| panic!("Can't find Bundle Vec for: {:?}", &window_id)).get(data.0).unwrap_or_else(|| panic!("Can't find bundle of: {:?}", data.0));
+        {
+            let (bundles, batches) = match (self.sys.bundles_map.get_mut(&window_id), self.sys.batches_map.get_mut(&window_id)) {
+                (Some(bundles), Some(batches)) => (bundles, batches),
+                _ => {
+                    warn!("No bundles found for {:?}, sending a blank frame", window_id);
+                    send_encoder(&mut self.back_channel, window_id, encoder);
+                    return;
+                },
+            };
 
-            if let Some(texture_data) = data.2 {
-                encoder.update_constant_buffer(&b.get_data().texture_data, &texture_data);
-            }
+            for (_, view, proj, scissor, dirty_cam) in views {
+                encoder.clear_depth(&out.1, 1.0);
+
+                let mut start = 0;
+
+                while start < datas.len() {
+                    let layer = datas[start].1;
+                    let end = datas[start..].iter().position(|data| data.1 != layer).map_or(datas.len(), |offset| start + offset);
+
+                    for data in &datas[start..end] {
+                        if data.2 {
+                            match batches.get_mut(data.0) {
+                                Some(batch) => batch.push(data.4.into(), &data.3),
+                                None => warn!("Can't find batch of: {:?}", data.0),
+                            }
+                            continue;
+                        }
+
+                        // Batched alpha sprites go under anything blended after them in the layer
+                        if data.7.get_blend_mode().get_order() > BlendMode::Alpha.get_order() {
+                            encode_batches(batches, &mut encoder, view.into(), proj.into(), scissor, &window_id);
+                        }
+
+                        let b = match bundles.get_mut(data.0) {
+                            Some(b) => b,
+                            None => {
+                                warn!("Can't find bundle of: {:?}", data.0);
+                                continue;
+                            },
+                        };
+
+                        encoder.update_constant_buffer(&b.get_data().texture_data, &data.3.clone().with_inset(b.get_texel_inset()));
+
+                        if let Some(ref material_data) = data.6 {
+                            encoder.update_constant_buffer(&b.get_data().material_data, material_data);
+                        }
+
+                        let mut projection_data = None;
+
+                        if true {//dirty_cam || transform.take_dirty() {
+                            projection_data = Some(
+                                ProjectionData {
+                                    model: data.4.into(),
+                                    view: view.into(),
+                                    proj: proj.into(),
+                                }
+                            );
+                        }
+
+                        if let Some(projection_data) = projection_data {
+                            encoder.update_constant_buffer(&b.get_data().projection_data, &projection_data);
+                        }
+
+                        b.get_mut_data().scissor = scissor;
+                        b.encode(&mut encoder, data.5, data.7);
+                    }
 
-            if let Some(projection_data) = data.3 {
-                encoder.update_constant_buffer(&b.get_data().projection_data, &projection_data);
-            }
+                    encode_batches(batches, &mut encoder, view.into(), proj.into(), scissor, &window_id);
 
-            b.encode(&mut encoder);
+                    start = end;
+                }
+            }
         }
 
-        self.back_channel.send_from((window_id, FromRender::Encoder(encoder)));
+        send_encoder(&mut self.back_channel, window_id, encoder);
     }
 
     fn set_graphics_data(&mut self, window_id: WindowId, out_color: OutColor, out_depth: OutDepth) {
-        *self.sys.outs.get_mut(&window_id).unwrap_or_else(|| panic!("Can't find out for window id: {:?}", window_id)) = (out_color.clone(), out_depth.clone());
+        match self.sys.outs.get_mut(&window_id) {
+            Some(out) => *out = (out_color.clone(), out_depth.clone()),
+            None => {
+                warn!("Ignoring graphics data for closed window: {:?}", window_id);
+                return;
+            },
+        }
 
-        for mut bundle in self.sys.bundles_map.get_mut(&window_id).unwrap_or_else(|| panic!("Can't find Bundle Vec for: {:?}", window_id)) {
-            bundle.get_mut_data().out_color = out_color.clone();
-            bundle.get_mut_data().out_depth = out_depth.clone();
+        if let Some(bundles) = self.sys.bundles_map.get_mut(&window_id) {
+            for mut bundle in bundles {
+                bundle.get_mut_data().out_color = out_color.clone();
+                bundle.get_mut_data().out_depth = out_depth.clone();
+            }
+        }
+
+        if let Some(batches) = self.sys.batches_map.get_mut(&window_id) {
+            for batch in batches {
+                batch.get_mut_data().out_color = out_color.clone();
+                batch.get_mut_data().out_depth = out_depth.clone();
+            }
         }
     }
 
-    fn process_event(&mut self, arg: &RunArg, event: WindowedToRender) -> bool {
+    fn process_event(&mut self, arg: &RunArg, event: WindowedToRender, alpha: GfxCoord) -> bool {
         match event {
             (window_id, ToRender::Encoder(encoder)) => {
-                self.render(arg, window_id, encoder);
+                self.render(arg, window_id, encoder, alpha);
                 false
             },
             (window_id, ToRender::GraphicsData(out_color, out_depth)) => {
                 self.set_graphics_data(window_id, out_color, out_depth);
                 true
             },
+            (window_id, ToRender::OpenWindow(out_color, out_depth, clear_color, bundles, batches)) => {
+                warn!("Render System opening window: {:?}", window_id);
+                self.sys.add_window(window_id, out_color, out_depth, clear_color, bundles, batches);
+                true
+            },
+            (window_id, ToRender::ReplaceBundles(bundles, batches)) => {
+                warn!("Render System replacing bundles of: {:?}", window_id);
+                self.sys.replace_bundles(window_id, bundles, batches);
+                true
+            },
+            (window_id, ToRender::CloseWindow) => {
+                warn!("Render System closing window: {:?}", window_id);
+                self.sys.remove_window(&window_id);
+                true
+            },
+            (_, ToRender::Quit) => {
+                warn!("Render System received Quit");
+                self.quitting = true;
+                false
+            },
         }
     }
 }
 
 pub struct RenderSystemSend {
     outs: HashMap<WindowId, (OutColor, OutDepth)>,
+    clear_colors: HashMap<WindowId, [f32; 4]>,
     bundles_map: HashMap<WindowId, Vec<Bundle>>,
-    shaders: Shaders,
+    batches_map: HashMap<WindowId, Vec<SpriteBatch>>,
+    materials: Materials,
+    batch_shaders: Shaders,
 }
 
 impl RenderSystemSend {
-    pub fn new(outs: HashMap<WindowId, (OutColor, OutDepth)>) -> RenderSystemSend {
+    pub fn new(outs: HashMap<WindowId, (OutColor, OutDepth)>, clear_colors: HashMap<WindowId, [f32; 4]>, materials: Materials, batch_shaders: Shaders) -> RenderSystemSend {
         warn!("Creating Render System Struct");
         RenderSystemSend {
             outs: outs,
+            clear_colors: clear_colors,
             bundles_map: HashMap::new(),
-            shaders: make_shaders(),
+            batches_map: HashMap::new(),
+            materials: materials,
+            batch_shaders: batch_shaders,
         }
     }
 
     pub fn add_render(&mut self,
         window_id: WindowId,
         factory: &mut GlFactory,
+        cache: &mut PipelineCache,
         packet: &Packet,
-        texture: RlTexture
-    ) -> RenderId {
-        warn!("Creating Shader Set");
-        let shader_set = factory.create_shader_set(self.shaders.get_vertex_shader(), self.shaders.get_fragment_shader()).unwrap_or_else(|err| panic!("Create Shader Set Error: {:?}", err));
-
-        warn!("Creating Program");
-        let program = factory.create_program(&shader_set).unwrap_or_else(|err| panic!("Create Program Error: {:?}", err));
-
-        warn!("Creating Pipeline from Program");
-        let pso = factory.create_pipeline_from_program(
-            &program,
-            Primitive::TriangleList,
-            packet.get_rasterizer(),
-            pipe::new()
-        ).unwrap_or_else(|err| panic!("Create Pipeline from Program Error: {:?}", err));
-
-        warn!("Creating Sampler Info");
-        let sampler_info = SamplerInfo::new(
-            FilterMethod::Scale,
-            WrapMode::Mirror,
-        );
-
-        warn!("Creating Vertex Buffer");
-        let (vbuf, slice) = factory.create_vertex_buffer_with_slice(packet.get_vertices(), packet.get_indices());
-
-        warn!("Creating Pipe Data");
-        let data = pipe::Data {
-            vbuf: vbuf,
-            spritesheet: (texture, factory.create_sampler(sampler_info)),
-            texture_data: factory.create_constant_buffer(1),
-            projection_data: factory.create_constant_buffer(1),
-            out_color: self.outs.get(&window_id).unwrap_or_else(|| panic!("Can't find outs for: {:?}", window_id)).0.clone(),
-            out_depth: self.outs.get(&window_id).unwrap_or_else(|| panic!("Can't find outs for: {:?}", window_id)).1.clone(),
-        };
+        texture: RlTexture,
+        texture_settings: &TextureSettings
+    ) -> Result<RenderId, GraphicsError> {
+        let (out_color, out_depth) = try!(self.outs.get(&window_id).cloned().ok_or_else(|| GraphicsError::Pipeline(format!("no render targets for {:?}", window_id))));
 
-        warn!("Creating Id");
+        let bundle = try!(make_bundle(factory, cache, &self.materials, packet, texture.clone(), texture_settings, out_color.clone(), out_depth.clone()));
 
+        let batch = try!(make_sprite_batch(factory, cache, &self.batch_shaders, packet, texture, texture_settings, out_color, out_depth, DEFAULT_BATCH_CAPACITY));
 
         warn!("Getting Bundles as Mutable");
-        let mut bundles = {
-            if self.bundles_map.get(&window_id).is_some() {
-                self.bundles_map.get_mut(&window_id).unwrap_or_else(|| panic!("Can't find Bundle Vec for: {:?}", window_id))
-            } else {
-                self.bundles_map.insert(window_id.clone(), vec!());
-                self.bundles_map.get_mut(&window_id).unwrap_or_else(|| panic!("Can't find Bundle Vec for: {:?}", window_id))
-            }
-        };
+        let mut bundles = self.bundles_map.entry(window_id.clone()).or_insert_with(|| vec!());
 
         let id = bundles.len();
 
         warn!("Adding new bundle to Bundles");
-        bundles.push(Bundle::new(slice, pso, data));
+        bundles.push(bundle);
+
+        self.batches_map.entry(window_id.clone()).or_insert_with(|| vec!()).push(batch);
 
         warn!("Returning Render Id");
-        RenderId::new(window_id.clone(), id)
+        Ok(RenderId::new(window_id.clone(), id))
+    }
+
+    pub fn add_window(&mut self, window_id: WindowId, out_color: OutColor, out_depth: OutDepth, clear_color: [f32; 4], bundles: Vec<Bundle>, batches: Vec<SpriteBatch>) {
+        self.outs.insert(window_id.clone(), (out_color, out_depth));
+        self.clear_colors.insert(window_id.clone(), clear_color);
+        self.bundles_map.insert(window_id.clone(), bundles);
+        self.batches_map.insert(window_id, batches);
+    }
+
+    pub fn replace_bundles(&mut self, window_id: WindowId, bundles: Vec<Bundle>, batches: Vec<SpriteBatch>) {
+        if !self.outs.contains_key(&window_id) {
+            warn!("Ignoring bundles for closed window: {:?}", window_id);
+            return;
+        }
+
+        self.bundles_map.insert(window_id.clone(), bundles);
+        self.batches_map.insert(window_id, batches);
+    }
+
+    pub fn remove_window(&mut self, window_id: &WindowId) {
+        self.outs.remove(window_id);
+        self.clear_colors.remove(window_id);
+        self.bundles_map.remove(window_id);
+        self.batches_map.remove(window_id);
+    }
+}
+
+// Free so it can be called while the bundles are borrowed out of self
+fn send_encoder(back_channel: &mut BackChannel<WindowedToRender, WindowedFromRender>, window_id: WindowId, encoder: Encoder) {
+    if let Err(err) = back_channel.send_from((window_id, FromRender::Encoder(encoder))) {
+        warn!("Render System unable to return encoder: {}", err);
     }
 }
 
-impl System<Delta> for RenderSystem {
-    fn run(&mut self, arg: RunArg, _: Delta) {
-        let mut event = self.back_channel.recv_to();
-        while self.process_event(&arg, event) {
-            event = self.back_channel.recv_to();
+fn encode_batches(batches: &mut Vec<SpriteBatch>, encoder: &mut Encoder, view: [[f32; 4]; 4], proj: [[f32; 4]; 4], scissor: ScissorRect, window_id: &WindowId) {
+    for batch in batches.iter_mut() {
+        if let Err(err) = batch.encode(encoder, view, proj, scissor) {
+            warn!("Unable to encode sprite batch for {:?}: {}", window_id, err);
+            batch.clear();
+        }
+    }
+}
+
+fn viewport_scissor(viewport: &Viewport, width: u16, height: u16) -> ScissorRect {
+    ScissorRect {
+        x: (viewport.get_x() * width as GfxCoord) as u16,
+        y: (viewport.get_y() * height as GfxCoord) as u16,
+        w: (viewport.get_width() * width as GfxCoord) as u16,
+        h: (viewport.get_height() * height as GfxCoord) as u16,
+    }
+}
+
+pub fn make_bundle(
+    factory: &mut GlFactory,
+    cache: &mut PipelineCache,
+    materials: &Materials,
+    packet: &Packet,
+    texture: RlTexture,
+    texture_settings: &TextureSettings,
+    out_color: OutColor,
+    out_depth: OutDepth
+) -> Result<Bundle, GraphicsError> {
+    let mut psos = HashMap::new();
+
+    for &(material, ref shaders) in materials.iter() {
+        warn!("Getting Pipelines for Material: {}", material.get_name());
+        for render_state in RenderState::all() {
+            let pso = try!(cache.get_pipeline(factory, shaders, Primitive::TriangleList, packet.get_rasterizer(), render_state, make_pipe_init));
+            psos.insert((material, render_state), pso);
+        }
+    }
+
+    let sampler = cache.get_sampler(factory, texture_settings.get_sampler_info());
+
+    warn!("Creating Vertex Buffer");
+    let (vbuf, slice) = factory.create_vertex_buffer_with_slice(packet.get_vertices(), packet.get_indices());
+
+    let (width, height, _, _) = out_color.get_dimensions();
+
+    warn!("Creating Pipe Data");
+    let data = pipe::Data {
+        vbuf: vbuf,
+        spritesheet: (texture, sampler),
+        texture_data: factory.create_constant_buffer(1),
+        material_data: factory.create_constant_buffer(1),
+        projection_data: factory.create_constant_buffer(1),
+        out_color: out_color,
+        out_depth: out_depth,
+        scissor: ScissorRect {
+            x: 0,
+            y: 0,
+            w: width,
+            h: height,
+        },
+    };
+
+    Ok(Bundle::new(slice, psos, data, texture_settings.get_texel_inset()))
+}
+
+pub fn make_sprite_batch(
+    factory: &mut GlFactory,
+    cache: &mut PipelineCache,
+    shaders: &Shaders,
+    packet: &Packet,
+    texture: RlTexture,
+    texture_settings: &TextureSettings,
+    out_color: OutColor,
+    out_depth: OutDepth,
+    capacity: usize
+) -> Result<SpriteBatch, GraphicsError> {
+    warn!("Getting Batch Pipeline");
+    let pso = try!(cache.get_pipeline(factory, shaders, Primitive::TriangleList, packet.get_rasterizer(), RenderState::new_default(), make_batch_pipe_init));
+
+    let sampler = cache.get_sampler(factory, texture_settings.get_sampler_info());
+
+    warn!("Creating Dynamic Vertex Buffer for {} Sprites", capacity);
+    let vbuf = try!(factory.create_buffer_dynamic::<BatchVertex>(capacity * 4, BufferRole::Vertex, Bind::empty()).map_err(|err| GraphicsError::Pipeline(format!("{:?}", err))));
+
+    let slice = Slice {
+        start: 0,
+        end: 0,
+        base_vertex: 0,
+        instances: None,
+        buffer: make_batch_indices(capacity).as_slice().into_index_buffer(factory),
+    };
+
+    let (width, height, _, _) = out_color.get_dimensions();
+
+    let data = batch_pipe::Data {
+        vbuf: vbuf,
+        batch_data: factory.create_constant_buffer(1),
+        spritesheet: (texture, sampler),
+        out_color: out_color,
+        out_depth: out_depth,
+        scissor: ScissorRect {
+            x: 0,
+            y: 0,
+            w: width,
+            h: height,
+        },
+    };
+
+    Ok(SpriteBatch::new(slice, pso, data, capacity, texture_settings.get_texel_inset()))
+}
+
+impl System<Step> for RenderSystem {
+    fn run(&mut self, arg: RunArg, step: Step) {
+        let alpha = match step {
+            Step::Render(alpha) => alpha as GfxCoord,
+            Step::Tick(_, delta) => {
+                self.time += delta as GfxCoord;
+                arg.fetch(|_| ());
+                return;
+            },
+        };
+
+        while !self.quitting {
+            match self.back_channel.recv_to() {
+                Ok(event) => if !self.process_event(&arg, event, alpha) {
+                    break;
+                },
+                Err(err) => {
+                    warn!("Render System channel closed: {}", err);
+                    self.quitting = true;
+                },
+            }
+        }
+
+        if self.quitting {
+            arg.fetch(|_| ());
         }
     }
 }
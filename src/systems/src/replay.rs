@@ -0,0 +1,416 @@
+use std::collections::{VecDeque};
+use std::error::{Error};
+use std::fmt;
+use std::fs::{File};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use glutin::{MouseButton};
+
+use console::{ConsoleKey};
+use control::{ToControl, WindowedToControl};
+use input::{Action};
+use rng::{Seed};
+use utils::{TickNum, WindowId};
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(PathBuf, io::Error),
+    Parse(PathBuf, usize, String),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ReplayError::Io(ref path, ref err) => write!(f, "unable to access replay {:?}: {}", path, err),
+            ReplayError::Parse(ref path, line, ref desc) => write!(f, "unable to parse replay {:?}:{}: {}", path, line, desc),
+        }
+    }
+}
+
+impl Error for ReplayError {
+    fn description(&self) -> &str {
+        match *self {
+            ReplayError::Io(..) => "replay io error",
+            ReplayError::Parse(..) => "replay parse error",
+        }
+    }
+
+    fn cause(&self) -> Option<&Error> {
+        match *self {
+            ReplayError::Io(_, ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum InputLog {
+    Live,
+    Record(InputRecorder),
+    Replay(InputReplay),
+}
+
+impl InputLog {
+    pub fn process(&mut self, tick: TickNum, events: Vec<WindowedToControl>) -> Vec<WindowedToControl> {
+        match *self {
+            InputLog::Live => events,
+            InputLog::Record(ref mut recorder) => {
+                recorder.record(tick, &events);
+                events
+            },
+            InputLog::Replay(ref mut replay) => replay.merge(tick, events),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct InputRecorder {
+    path: PathBuf,
+    writer: Option<BufWriter<File>>,
+}
+
+impl InputRecorder {
+    pub fn create<P>(path: P, seed: Seed, ticks_per_second: u32) -> Result<InputRecorder, ReplayError>
+    where P: AsRef<Path> {
+        let path = path.as_ref().to_path_buf();
+
+        let mut writer = BufWriter::new(try!(File::create(&path).map_err(|err| ReplayError::Io(path.clone(), err))));
+        try!(write!(writer, "seed {}\nticks_per_second {}\n", seed, ticks_per_second).map_err(|err| ReplayError::Io(path.clone(), err)));
+
+        Ok(InputRecorder {
+            path: path,
+            writer: Some(writer),
+        })
+    }
+
+    pub fn record(&mut self, tick: TickNum, events: &[WindowedToControl]) {
+        let lines: Vec<String> = events.iter().filter_map(|&(ref window_id, ref event)|
+            format_event(event).map(|text| format!("{} {} {}\n", tick, window_id.0, text))
+        ).collect();
+
+        if lines.is_empty() {
+            return;
+        }
+
+        let result = match self.writer {
+            Some(ref mut writer) => lines.iter().map(|line| writer.write_all(line.as_bytes())).collect::<Result<Vec<()>, io::Error>>().and_then(|_| writer.flush()),
+            None => return,
+        };
+
+        if let Err(err) = result {
+            error!("Unable to write replay {:?}, recording stopped: {}", self.path, err);
+            self.writer = None;
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct InputReplay {
+    seed: Seed,
+    ticks_per_second: u32,
+    events: VecDeque<(TickNum, WindowedToControl)>,
+    finished: bool,
+}
+
+impl InputReplay {
+    pub fn load<P>(path: P) -> Result<InputReplay, ReplayError>
+    where P: AsRef<Path> {
+        let path = path.as_ref();
+        let file = try!(File::open(path).map_err(|err| ReplayError::Io(path.to_path_buf(), err)));
+
+        InputReplay::read(path, BufReader::new(file))
+    }
+
+    // The path is only used to name the replay in errors
+    pub fn read<R>(path: &Path, reader: R) -> Result<InputReplay, ReplayError>
+    where R: BufRead {
+        let mut seed = None;
+        let mut ticks_per_second = None;
+        let mut events = VecDeque::new();
+        let mut last_tick = 0;
+
+        for (index, line) in reader.lines().enumerate() {
+            let line = try!(line.map_err(|err| ReplayError::Io(path.to_path_buf(), err)));
+            let parse_error = |desc: String| ReplayError::Parse(path.to_path_buf(), index + 1, desc);
+
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let parts: Vec<&str> = line.splitn(4, ' ').collect();
+
+            match parts[0] {
+                "seed" => {
+                    seed = Some(try!(parts.get(1).and_then(|seed| seed.parse().ok()).ok_or_else(|| parse_error("expected seed number".to_string()))));
+                    continue;
+                },
+                "ticks_per_second" => {
+                    ticks_per_second = Some(try!(parts.get(1).and_then(|ticks| ticks.parse().ok()).ok_or_else(|| parse_error("expected ticks per second".to_string()))));
+                    continue;
+                },
+                _ => (),
+            }
+
+            let tick: TickNum = try!(parts[0].parse().map_err(|_| parse_error(format!("expected tick number, found {:?}", parts[0]))));
+            let window_id: u32 = try!(parts.get(1).and_then(|window_id| window_id.parse().ok()).ok_or_else(|| parse_error("expected window id".to_string())));
+            let kind = try!(parts.get(2).cloned().ok_or_else(|| parse_error("missing event".to_string())));
+            let rest = parts.get(3).cloned().unwrap_or("");
+
+            if tick < last_tick {
+                return Err(parse_error(format!("tick {} comes after tick {}", tick, last_tick)));
+            }
+            last_tick = tick;
+
+            let event = try!(parse_event(kind, rest).ok_or_else(|| parse_error(format!("invalid event `{} {}`", kind, rest))));
+
+            events.push_back((tick, (WindowId(window_id), event)));
+        }
+
+        Ok(InputReplay {
+            seed: try!(seed.ok_or_else(|| ReplayError::Parse(path.to_path_buf(), 0, "missing seed".to_string()))),
+            ticks_per_second: try!(ticks_per_second.ok_or_else(|| ReplayError::Parse(path.to_path_buf(), 0, "missing ticks_per_second".to_string()))),
+            events: events,
+            finished: false,
+        })
+    }
+
+    pub fn get_seed(&self) -> Seed {
+        self.seed
+    }
+
+    pub fn get_ticks_per_second(&self) -> u32 {
+        self.ticks_per_second
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    pub fn merge(&mut self, tick: TickNum, live: Vec<WindowedToControl>) -> Vec<WindowedToControl> {
+        if self.finished {
+            return live;
+        }
+
+        let mut events: Vec<WindowedToControl> = live.into_iter().filter(|&(_, ref event)| !is_recorded(event) || is_quit(event)).collect();
+
+        while self.events.front().map_or(false, |&(event_tick, _)| event_tick <= tick) {
+            if let Some((_, event)) = self.events.pop_front() {
+                events.push(event);
+            }
+        }
+
+        if self.events.is_empty() {
+            warn!("Replay finished at tick {}, resuming live input", tick);
+            self.finished = true;
+        }
+
+        events
+    }
+}
+
+fn is_recorded(event: &ToControl) -> bool {
+    format_event(event).is_some()
+}
+
+fn is_quit(event: &ToControl) -> bool {
+    match *event {
+        ToControl::Quit => true,
+        _ => false,
+    }
+}
+
+fn format_event(event: &ToControl) -> Option<String> {
+    match *event {
+        ToControl::MouseMoved(x, y) => Some(format!("mouse_moved {} {}", x, y)),
+        ToControl::MouseInput(pressed, button) => Some(format!("mouse_input {} {}", format_pressed(pressed), format_mouse_button(button))),
+        ToControl::Action(action, pressed) => Some(format!("action {} {}", action.get_name(), format_pressed(pressed))),
+        ToControl::ActionRepeat(action) => Some(format!("action_repeat {}", action.get_name())),
+        ToControl::Focus(focused) => Some(format!("focus {}", if focused { "gained" } else { "lost" })),
+        ToControl::Resize(width, height) => Some(format!("resize {} {}", width, height)),
+        ToControl::ConsoleToggle(open) => Some(format!("console_toggle {}", if open { "open" } else { "closed" })),
+        ToControl::ConsoleText(ref text) => Some(format!("console_text {}", text.replace('\n', " "))),
+        ToControl::ConsoleKey(key) => Some(format!("console_key {}", key.get_name())),
+        ToControl::Quit => Some("quit".to_string()),
+        ToControl::WindowOpened(..) | ToControl::WindowClosed => None,
+    }
+}
+
+fn parse_event(kind: &str, rest: &str) -> Option<ToControl> {
+    if kind == "console_text" {
+        return Some(ToControl::ConsoleText(rest.to_string()));
+    }
+
+    let args: Vec<&str> = rest.split_whitespace().collect();
+    let arg = |index: usize| args.get(index).cloned().unwrap_or("");
+
+    let expected = match kind {
+        "quit" => 0,
+        "action_repeat" | "focus" | "console_toggle" | "console_key" => 1,
+        _ => 2,
+    };
+
+    if args.len() != expected {
+        return None;
+    }
+
+    match kind {
+        "mouse_moved" => match (arg(0).parse(), arg(1).parse()) {
+            (Ok(x), Ok(y)) => Some(ToControl::MouseMoved(x, y)),
+            _ => None,
+        },
+        "mouse_input" => match (parse_pressed(arg(0)), parse_mouse_button(arg(1))) {
+            (Some(pressed), Some(button)) => Some(ToControl::MouseInput(pressed, button)),
+            _ => None,
+        },
+        "action" => match (Action::from_name(arg(0)), parse_pressed(arg(1))) {
+            (Some(action), Some(pressed)) => Some(ToControl::Action(action, pressed)),
+            _ => None,
+        },
+        "action_repeat" => Action::from_name(arg(0)).map(ToControl::ActionRepeat),
+        "focus" => match arg(0) {
+            "gained" => Some(ToControl::Focus(true)),
+            "lost" => Some(ToControl::Focus(false)),
+            _ => None,
+        },
+        "resize" => match (arg(0).parse(), arg(1).parse()) {
+            (Ok(width), Ok(height)) => Some(ToControl::Resize(width, height)),
+            _ => None,
+        },
+        "console_toggle" => match arg(0) {
+            "open" => Some(ToControl::ConsoleToggle(true)),
+            "closed" => Some(ToControl::ConsoleToggle(false)),
+            _ => None,
+        },
+        "console_key" => ConsoleKey::from_name(arg(0)).map(ToControl::ConsoleKey),
+        "quit" => Some(ToControl::Quit),
+        _ => None,
+    }
+}
+
+fn format_pressed(pressed: bool) -> &'static str {
+    if pressed {
+        "pressed"
+    } else {
+        "released"
+    }
+}
+
+fn parse_pressed(text: &str) -> Option<bool> {
+    match text {
+        "pressed" => Some(true),
+        "released" => Some(false),
+        _ => None,
+    }
+}
+
+fn format_mouse_button(button: MouseButton) -> String {
+    match button {
+        MouseButton::Left => "left".to_string(),
+        MouseButton::Right => "right".to_string(),
+        MouseButton::Middle => "middle".to_string(),
+        MouseButton::Other(id) => id.to_string(),
+    }
+}
+
+fn parse_mouse_button(text: &str) -> Option<MouseButton> {
+    match text {
+        "left" => Some(MouseButton::Left),
+        "right" => Some(MouseButton::Right),
+        "middle" => Some(MouseButton::Middle),
+        other => other.parse().ok().map(MouseButton::Other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path};
+
+    use glutin::{MouseButton};
+
+    use console::{ConsoleKey};
+    use control::{ToControl};
+    use input::{Action};
+    use utils::{WindowId};
+
+    use super::{format_event, parse_event, InputReplay, ReplayError};
+
+    fn split(text: &str) -> (&str, &str) {
+        let mut parts = text.splitn(2, ' ');
+        let kind = parts.next().unwrap_or("");
+        (kind, parts.next().unwrap_or(""))
+    }
+
+    fn round_trip(event: ToControl) {
+        let text = format_event(&event).expect("event should be recorded");
+        let (kind, rest) = split(&text);
+        let parsed = parse_event(kind, rest).unwrap_or_else(|| panic!("unable to parse `{}`", text));
+        assert_eq!(format_event(&parsed), Some(text));
+    }
+
+    #[test]
+    fn recorded_events_round_trip() {
+        round_trip(ToControl::MouseMoved(12, 480));
+        round_trip(ToControl::MouseInput(true, MouseButton::Left));
+        round_trip(ToControl::MouseInput(false, MouseButton::Other(7)));
+        round_trip(ToControl::Action(Action::MoveUp, true));
+        round_trip(ToControl::Action(Action::PickUp, false));
+        round_trip(ToControl::ActionRepeat(Action::MoveLeft));
+        round_trip(ToControl::Focus(false));
+        round_trip(ToControl::Resize(1024, 768));
+        round_trip(ToControl::ConsoleToggle(true));
+        round_trip(ToControl::ConsoleText("timescale 0.5".to_string()));
+        round_trip(ToControl::ConsoleKey(ConsoleKey::HistoryPrev));
+        round_trip(ToControl::Quit);
+    }
+
+    #[test]
+    fn window_events_are_not_recorded() {
+        assert_eq!(format_event(&ToControl::WindowClosed), None);
+        assert_eq!(format_event(&ToControl::WindowOpened(vec!(), vec!())), None);
+    }
+
+    #[test]
+    fn console_text_keeps_one_line() {
+        assert_eq!(format_event(&ToControl::ConsoleText("a\nb".to_string())), Some("console_text a b".to_string()));
+    }
+
+    #[test]
+    fn rejects_bad_events() {
+        for text in &["action fly pressed", "action wait", "action wait down", "mouse_moved 1", "mouse_moved -1 2", "focus maybe", "resize 1 2 3", "quit now", "teleport 1 2"] {
+            let (kind, rest) = split(text);
+            assert!(parse_event(kind, rest).is_none(), "parsed `{}`", text);
+        }
+    }
+
+    #[test]
+    fn load_reads_header_and_events() {
+        let text = "seed 42\nticks_per_second 60\n# comment\n\n3 1 action wait pressed\n5 2 quit\n";
+        let mut replay = InputReplay::read(Path::new("test.replay"), text.as_bytes()).unwrap();
+
+        assert_eq!(replay.get_seed(), 42);
+        assert_eq!(replay.get_ticks_per_second(), 60);
+
+        assert!(replay.merge(2, vec!()).is_empty());
+
+        let events = replay.merge(4, vec!((WindowId(1), ToControl::Action(Action::Quit, true)), (WindowId(1), ToControl::WindowClosed)));
+        let texts: Vec<Option<String>> = events.iter().map(|&(_, ref event)| format_event(event)).collect();
+        assert_eq!(texts, vec!(None, Some("action wait pressed".to_string())));
+        assert!(!replay.is_finished());
+
+        let events = replay.merge(5, vec!());
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].0, WindowId(2));
+        assert!(replay.is_finished());
+    }
+
+    #[test]
+    fn load_rejects_ticks_out_of_order() {
+        let text = "seed 1\nticks_per_second 60\n5 1 quit\n3 1 quit\n";
+
+        match InputReplay::read(Path::new("test.replay"), text.as_bytes()) {
+            Err(ReplayError::Parse(_, line, _)) => assert_eq!(line, 4),
+            other => panic!("expected a parse error, found {:?}", other),
+        }
+    }
+}
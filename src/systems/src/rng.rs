@@ -0,0 +1,26 @@
+use rand::{SeedableRng, XorShiftRng};
+
+pub type Seed = u64;
+
+#[derive(Debug)]
+pub struct GameRng {
+    seed: Seed,
+    rng: XorShiftRng,
+}
+
+impl GameRng {
+    pub fn new(seed: Seed) -> GameRng {
+        GameRng {
+            seed: seed,
+            rng: XorShiftRng::from_seed([seed as u32, (seed >> 32) as u32, 0x193a6754, 0xa8a7d469]),
+        }
+    }
+
+    pub fn get_seed(&self) -> Seed {
+        self.seed
+    }
+
+    pub fn get_mut_rng(&mut self) -> &mut XorShiftRng {
+        &mut self.rng
+    }
+}
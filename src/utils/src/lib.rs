@@ -2,13 +2,16 @@
 extern crate log;
 
 pub mod fps_counter;
+pub mod timestep;
 
 pub use fps_counter::FpsCounter;
+pub use timestep::{FixedTimestep, Step};
 
 pub type Delta = f64;
+pub type TickNum = u64;
 pub type Coord = f64;
 pub type CoordI = i64;
 pub type GfxCoord = f32;
 
-#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
 pub struct WindowId(pub u32);
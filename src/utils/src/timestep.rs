@@ -0,0 +1,144 @@
+use ::{Delta, TickNum};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Step {
+    Tick(TickNum, Delta),
+    Render(Delta),
+}
+
+#[derive(Debug)]
+pub struct FixedTimestep {
+    tick_length: Delta,
+    accumulator: Delta,
+    max_ticks_per_frame: u32,
+    frame_ticks: u32,
+    ticks: TickNum,
+}
+
+impl FixedTimestep {
+    pub fn new(ticks_per_second: u32, max_ticks_per_frame: u32) -> FixedTimestep {
+        assert!(ticks_per_second > 0, "ticks per second must be above zero");
+        assert!(max_ticks_per_frame > 0, "max ticks per frame must be above zero");
+
+        FixedTimestep {
+            tick_length: 1.0 / ticks_per_second as Delta,
+            accumulator: 0.0,
+            max_ticks_per_frame: max_ticks_per_frame,
+            frame_ticks: 0,
+            ticks: 0,
+        }
+    }
+
+    pub fn accumulate(&mut self, delta: Delta) {
+        self.accumulator += delta;
+        self.frame_ticks = 0;
+    }
+
+    pub fn take_tick(&mut self) -> Option<TickNum> {
+        if self.accumulator < self.tick_length {
+            return None;
+        }
+
+        if self.frame_ticks >= self.max_ticks_per_frame {
+            let remainder = self.accumulator % self.tick_length;
+            warn!("Dropping {} seconds of simulation time", self.accumulator - remainder);
+            self.accumulator = remainder;
+            return None;
+        }
+
+        self.accumulator -= self.tick_length;
+        self.frame_ticks += 1;
+
+        let tick = self.ticks;
+        self.ticks += 1;
+        Some(tick)
+    }
+
+    pub fn get_tick_length(&self) -> Delta {
+        self.tick_length
+    }
+
+    pub fn get_ticks(&self) -> TickNum {
+        self.ticks
+    }
+
+    pub fn get_alpha(&self) -> Delta {
+        self.accumulator / self.tick_length
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FixedTimestep};
+
+    fn take_ticks(timestep: &mut FixedTimestep) -> Vec<u64> {
+        let mut ticks = vec!();
+        while let Some(tick) = timestep.take_tick() {
+            ticks.push(tick);
+        }
+        ticks
+    }
+
+    #[test]
+    fn accumulate_gives_whole_ticks() {
+        let mut timestep = FixedTimestep::new(4, 8);
+
+        timestep.accumulate(0.125);
+        assert_eq!(take_ticks(&mut timestep), vec!());
+
+        timestep.accumulate(0.25);
+        assert_eq!(take_ticks(&mut timestep), vec!(0));
+
+        timestep.accumulate(1.0);
+        assert_eq!(take_ticks(&mut timestep), vec!(1, 2, 3, 4));
+        assert_eq!(timestep.get_ticks(), 5);
+    }
+
+    #[test]
+    fn alpha_stays_below_one() {
+        let mut timestep = FixedTimestep::new(60, 8);
+
+        for &delta in &[0.0, 0.001, 0.016, 0.017, 0.05, 0.1, 1.0 / 60.0] {
+            timestep.accumulate(delta);
+            take_ticks(&mut timestep);
+
+            let alpha = timestep.get_alpha();
+            assert!(alpha >= 0.0 && alpha < 1.0, "alpha {} out of range after {}", alpha, delta);
+        }
+    }
+
+    #[test]
+    fn alpha_is_the_fraction_of_a_tick_left() {
+        let mut timestep = FixedTimestep::new(4, 8);
+
+        timestep.accumulate(0.375);
+        assert_eq!(take_ticks(&mut timestep), vec!(0));
+        assert!((timestep.get_alpha() - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn ticks_are_clamped_per_frame() {
+        let mut timestep = FixedTimestep::new(10, 3);
+
+        timestep.accumulate(1.0);
+        assert_eq!(take_ticks(&mut timestep), vec!(0, 1, 2));
+
+        // The clamped time is gone, the next frame starts fresh
+        timestep.accumulate(0.0);
+        assert_eq!(take_ticks(&mut timestep), vec!());
+    }
+
+    #[test]
+    fn clamping_keeps_only_the_partial_tick() {
+        let mut timestep = FixedTimestep::new(10, 2);
+
+        timestep.accumulate(0.55);
+        assert_eq!(take_ticks(&mut timestep).len(), 2);
+
+        // 0.35 was left over, 0.3 of it is dropped and the partial 0.05 kept
+        assert!((timestep.get_alpha() - 0.5).abs() < 1e-6);
+
+        timestep.accumulate(0.05);
+        assert_eq!(take_ticks(&mut timestep), vec!(2));
+    }
+}